@@ -1,13 +1,15 @@
 use autosnap::generator::{Options, Version};
-use autosnap::snap::SNAPCRAFT_YAML;
+use autosnap::snap::{GUI_DIRECTORY, SNAPCRAFT_YAML, THIRD_PARTY_LICENSES};
 use autosnap::{fetch_source, package_source};
 
 use clap::{crate_authors, crate_version, App, AppSettings, Arg};
 use log::LevelFilter;
 use simple_logger::SimpleLogger;
 use std::error::Error;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
 use std::str::FromStr;
-use std::{fs, process};
+use std::{fs, io, process};
 use url::Url;
 
 fn main() {
@@ -32,6 +34,35 @@ fn main() {
                 .default_value("git")
                 .help("Set the snap version (git, auto, or fixed: 0.2.0)"),
         )
+        .arg(
+            Arg::with_name("python-exec")
+                .long("python-exec")
+                .help("Allow executing setup.py to retrieve missing package metadata"),
+        )
+        .arg(
+            Arg::with_name("rust-features")
+                .long("rust-features")
+                .takes_value(true)
+                .use_delimiter(true)
+                .help("The Cargo features to enable (example: full,tls)"),
+        )
+        .arg(
+            Arg::with_name("rust-no-default-features")
+                .long("rust-no-default-features")
+                .help("Disable the Cargo default features"),
+        )
+        .arg(
+            Arg::with_name("rust-locked")
+                .long("rust-locked")
+                .help("Build using the dependencies versions from Cargo.lock"),
+        )
+        .arg(
+            Arg::with_name("combined-license")
+                .long("combined-license")
+                .help(
+                "Set the snap license to the combined license of the source and its dependencies",
+            ),
+        )
         .setting(AppSettings::ArgRequiredElseHelp)
         .get_matches();
 
@@ -65,6 +96,13 @@ fn main() {
     let options = Options {
         snap_version: Version::from(matches.value_of("snap-version").unwrap()),
         source_name: "".to_string(),
+        python_exec: matches.is_present("python-exec"),
+        rust_features: matches
+            .values_of("rust-features")
+            .map(|features| features.map(|f| f.to_string()).collect()),
+        rust_no_default_features: matches.is_present("rust-no-default-features"),
+        rust_locked: matches.is_present("rust-locked"),
+        combined_license: matches.is_present("combined-license"),
     };
 
     // package the source code
@@ -91,6 +129,56 @@ fn main() {
         process::exit(1);
     }
 
+    // write hooks script inside snap/hooks
+    for (name, hook) in &snap.hooks {
+        if let Some(script) = &hook.script {
+            let hook_path = path.join("snap").join("hooks").join(name);
+            if let Err(e) = write_hook(&hook_path, script) {
+                log::error!(
+                    "Error encountered while writing {}: {}",
+                    hook_path.display(),
+                    e
+                );
+                process::exit(1);
+            }
+            log::info!("The {} hook is stored at {}", name, hook_path.display());
+        }
+    }
+
+    // write desktop entries & icon inside snap/gui
+    if !snap.gui_files.is_empty() {
+        let gui_path = path.join(GUI_DIRECTORY);
+        if let Err(e) = snap.write_gui_files(&path) {
+            log::error!(
+                "Error encountered while writing {}: {}",
+                gui_path.display(),
+                e
+            );
+            process::exit(1);
+        }
+        log::info!(
+            "The graphical resources are stored at {}",
+            gui_path.display()
+        );
+    }
+
+    // write the third-party licenses report inside the source root
+    if let Some(report) = &snap.third_party_licenses {
+        let report_path = path.join(THIRD_PARTY_LICENSES);
+        if let Err(e) = fs::write(&report_path, report) {
+            log::error!(
+                "Error encountered while writing {}: {}",
+                THIRD_PARTY_LICENSES,
+                e
+            );
+            process::exit(1);
+        }
+        log::info!(
+            "The third-party licenses are listed in {}",
+            report_path.display()
+        );
+    }
+
     log::info!("Successfully packaged {}!", snap.name);
     log::info!(
         "The snapcraft file is stored at {}",
@@ -102,6 +190,14 @@ fn main() {
     );
 }
 
+fn write_hook(path: &Path, script: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, script)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
 fn configure_logging(log_level: &str) -> Result<(), Box<dyn Error>> {
     let log_level = LevelFilter::from_str(log_level)?;
 
@@ -3,19 +3,32 @@ use std::path::Path;
 use std::{fs, io};
 
 use askalono::{Store, TextData};
+use walkdir::{DirEntry, WalkDir};
 
+use crate::generator::daemon::Services;
+use crate::generator::desktop::GuiResources;
 use crate::generator::go::GoProvider;
+use crate::generator::hooks::DefaultConfig;
+use crate::generator::licenses::{DependencyLock, ThirdPartyLicenses};
 use crate::generator::python::PythonProvider;
+use crate::generator::readme::{extract_description, find_readme, summarize};
 use crate::generator::rust::RustProvider;
 use crate::snap::{App, File, Part};
 use crate::Result;
 
+mod daemon;
+mod desktop;
 mod go;
+mod hooks;
+mod licenses;
 mod python;
+mod readme;
 mod rust;
 
 static LICENSE_CACHE: &[u8] = include_bytes!("embedded-cache.bin.zstd");
 
+const IGNORED_DIRECTORIES: [&str; 4] = ["target", "vendor", "node_modules", "snap"];
+
 /// This enum describe the snap version strategy:
 /// i.e how the snap version will be set.
 #[derive(PartialEq, Clone)]
@@ -45,23 +58,56 @@ pub struct Options {
     /// The snap version strategy
     pub snap_version: Version,
     pub source_name: String,
+    /// Allow executing the source code to retrieve metadata (i.e python setup.py)
+    pub python_exec: bool,
+    /// The Cargo features to enable (auto-detected if not set)
+    pub rust_features: Option<Vec<String>>,
+    /// Disable the Cargo default features
+    pub rust_no_default_features: bool,
+    /// Build using the Cargo.lock dependencies versions (cargo --locked)
+    pub rust_locked: bool,
+    /// Set the snap license to the combined license of the source and its dependencies
+    pub combined_license: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            snap_version: Version::Git,
+            source_name: "".to_string(),
+            python_exec: false,
+            rust_features: None,
+            rust_no_default_features: false,
+            rust_locked: false,
+            combined_license: false,
+        }
+    }
 }
 
 trait Provider<G: Generator> {
-    fn provide<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<G>;
+    fn provide<P: AsRef<Path>>(source_path: P, options: &Options) -> Result<G>;
     fn can_provide<P: AsRef<Path>>(source_path: P) -> bool;
 }
 
 /// A `Generator` is an Autosnap extension that know how to package
 /// a specific language.
 pub trait Generator {
+    fn base(&self) -> Result<Option<String>>;
     fn name(&self) -> Result<Option<String>>;
     fn version(&self) -> Result<Option<String>>;
     fn summary(&self) -> Result<Option<String>>;
     fn description(&self) -> Result<Option<String>>;
     fn license(&self) -> Result<Option<String>>;
+    fn icon(&self) -> Result<Option<String>>;
+    fn website(&self) -> Result<Option<String>>;
+    fn source_code(&self) -> Result<Option<String>>;
+    fn issues(&self) -> Result<Option<String>>;
+    fn contact(&self) -> Result<Option<String>>;
+    fn grade(&self) -> Result<Option<String>>;
+    fn confinement(&self) -> Result<Option<String>>;
     fn parts(&self) -> Result<BTreeMap<String, Part>>;
     fn apps(&self) -> Result<BTreeMap<String, App>>;
+    fn lockfiles(&self) -> Result<Vec<DependencyLock>>;
 }
 
 /// The list of supported generators
@@ -75,26 +121,26 @@ impl Generators {
     /// Find the generator that can package given source
     fn find_generator<P: AsRef<Path>>(
         source_path: P,
-        source_name: &str,
+        options: &Options,
     ) -> Result<Box<dyn Generator>> {
         // TODO improve below
         if RustProvider::can_provide(&source_path) {
             log::debug!("Using RustGenerator");
-            let provider = RustProvider::provide(&source_path, source_name);
+            let provider = RustProvider::provide(&source_path, options);
             match provider {
                 Ok(v) => Ok(Box::new(v)),
                 Err(e) => Err(e),
             }
         } else if GoProvider::can_provide(&source_path) {
             log::debug!("Using GoGenerator");
-            let provider = GoProvider::provide(&source_path, source_name);
+            let provider = GoProvider::provide(&source_path, options);
             match provider {
                 Ok(v) => Ok(Box::new(v)),
                 Err(e) => Err(e),
             }
         } else if PythonProvider::can_provide(&source_path) {
             log::debug!("Using PythonGenerator");
-            let provider = PythonProvider::provide(&source_path, source_name);
+            let provider = PythonProvider::provide(&source_path, options);
             match provider {
                 Ok(v) => Ok(Box::new(v)),
                 Err(e) => Err(e),
@@ -108,42 +154,42 @@ impl Generators {
     ///
     /// ```no_run
     /// use autosnap::generator::{Generators, Options, Version};
-    /// let opts = Options{snap_version: Version::Git, source_name: "source-code".to_string()};
+    /// let opts = Options{snap_version: Version::Git, source_name: "source-code".to_string(), ..Default::default()};
     /// let file = Generators::generate("/tmp/source-code", &opts).unwrap();
     /// ```
     pub fn generate<P: AsRef<Path>>(source_path: P, options: &Options) -> Result<File> {
-        let generator = Generators::find_generator(&source_path, &options.source_name)?;
+        let generator = Generators::find_generator(&source_path, options)?;
 
         // Create snap with defaults set
         let mut snap = File::new(&options.source_name);
 
         // Set snap version as needed
         match &options.snap_version {
-            Version::Git => snap.version = "git".to_string(),
+            Version::Git => snap.version = Some("git".to_string()),
             Version::Fixed(version) => {
                 log::debug!("Set snap version to {}", version);
-                snap.version = version.clone()
+                snap.version = Some(version.clone())
             }
             _ => {}
         }
 
         // Try to autodetect license if possible
         if let Some((license, filename)) = find_license(&source_path)? {
-            let store = Store::from_cache(LICENSE_CACHE)?;
-            let result = store.analyze(&TextData::from(license));
-
-            // TODO use real value above
-            if result.score > 0.9 {
-                snap.license = result.name.to_string();
+            if let Some(license) = detect_license(&license)? {
                 log::debug!(
                     "Auto-detect snap license ({}) from file {}",
-                    result.name,
+                    license,
                     filename
                 );
+                snap.license = license;
             }
         }
 
         // Use generator to complete Snap
+        if let Some(base) = generator.base()? {
+            log::debug!("Set snap base to `{}`", base);
+            snap.base = base;
+        }
         if let Some(name) = generator.name()? {
             log::debug!("Set snap name to `{}`", name);
             snap.name = name;
@@ -152,21 +198,68 @@ impl Generators {
         if options.snap_version == Version::Auto {
             if let Some(version) = generator.version()? {
                 log::debug!("Set snap version to `{}`", version);
-                snap.version = version;
+                snap.version = Some(version);
             }
         }
+
+        // Extract description from the README, to be used if the generator doesn't provide one
+        let readme_description = match find_readme(&source_path) {
+            Ok(readme) => {
+                readme.and_then(|(readme, filename)| extract_description(&readme, &filename))
+            }
+            Err(e) => {
+                log::warn!("Unable to read the README: {}", e);
+                None
+            }
+        };
+
         if let Some(summary) = generator.summary()? {
             log::debug!("Set snap summary to `{}`", summary);
-            snap.summary = summary;
+            snap.summary = Some(summary);
+        } else if let Some(description) = &readme_description {
+            let summary = summarize(description);
+            log::debug!("Set snap summary to `{}` (from README)", summary);
+            snap.summary = Some(summary);
         }
         if let Some(description) = generator.description()? {
             log::debug!("Set snap description to `{}`", description);
-            snap.description = description;
+            snap.description = Some(description);
+        } else if let Some(description) = readme_description {
+            log::debug!("Set snap description to `{}` (from README)", description);
+            snap.description = Some(description);
         }
         if let Some(license) = generator.license()? {
             log::debug!("Set snap license to `{}`", license);
             snap.license = license;
         }
+        if let Some(icon) = generator.icon()? {
+            log::debug!("Set snap icon to `{}`", icon);
+            snap.icon = Some(icon);
+        }
+        if let Some(website) = generator.website()? {
+            log::debug!("Set snap website to `{}`", website);
+            snap.website = Some(website);
+        }
+        if let Some(source_code) = generator.source_code()? {
+            log::debug!("Set snap source-code to `{}`", source_code);
+            snap.source_code = Some(source_code);
+        }
+        if let Some(issues) = generator.issues()? {
+            log::debug!("Set snap issues to `{}`", issues);
+            snap.issues = Some(issues);
+        }
+        if let Some(contact) = generator.contact()? {
+            log::debug!("Set snap contact to `{}`", contact);
+            snap.contact = Some(contact);
+        }
+        if let Some(grade) = generator.grade()? {
+            log::debug!("Set snap grade to `{}`", grade);
+            snap.grade = grade;
+        }
+        if let Some(confinement) = generator.confinement()? {
+            log::debug!("Set snap confinement to `{}`", confinement);
+            snap.confinement = confinement;
+        }
 
         let parts = generator.parts()?;
         if parts.is_empty() {
@@ -180,10 +273,67 @@ impl Generators {
         }
         snap.apps = apps;
 
+        // Setup graphical applications using shipped desktop files & icons
+        GuiResources::find(&source_path)?.apply(&source_path, &mut snap)?;
+
+        // Setup daemons using shipped services definition
+        Services::find(&source_path)?.apply(&mut snap);
+
+        // Scaffold hooks to install the default configuration
+        if let Some(config) = DefaultConfig::find(&source_path)? {
+            config.apply(&mut snap);
+        }
+
+        // Inventory the licenses of the third-party dependencies
+        ThirdPartyLicenses::find(&generator.lockfiles()?)?
+            .apply(&mut snap, options.combined_license);
+
         Ok(snap)
     }
 }
 
+/// Walk through the given source tree (sorted by file name), skipping
+/// hidden, vendored and build directories.
+fn walk_source<P: AsRef<Path>>(source_path: P) -> impl Iterator<Item = walkdir::Result<DirEntry>> {
+    WalkDir::new(source_path)
+        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_entry(|e| {
+            let file_name = e.file_name().to_string_lossy();
+            e.depth() == 0
+                || !e.file_type().is_dir()
+                || !(file_name.starts_with('.')
+                    || IGNORED_DIRECTORIES.contains(&file_name.as_ref()))
+        })
+}
+
+/// Extract the executable name from a command line (i.e `env A=b /usr/bin/foo --bar` -> foo)
+fn executable_name(command: &str) -> Option<String> {
+    command
+        .split_whitespace()
+        .find(|arg| *arg != "env" && !arg.contains('='))
+        .and_then(|arg| Path::new(arg.trim_matches('"')).file_name())
+        .map(|f| f.to_string_lossy().to_string())
+}
+
+/// Detect the license (SPDX identifier) of given license text
+fn detect_license(text: &str) -> Result<Option<String>> {
+    let store = Store::from_cache(LICENSE_CACHE)?;
+    Ok(match_license(&store, text))
+}
+
+/// Match given license text against the licenses of given store
+fn match_license(store: &Store, text: &str) -> Option<String> {
+    let result = store.analyze(&TextData::from(text));
+
+    // TODO use real value above
+    if result.score > 0.9 {
+        Some(result.name.to_string())
+    } else {
+        None
+    }
+}
+
 /// Find the source license file. This naive method will try to find
 /// the project license file.
 fn find_license<P: AsRef<Path>>(source_path: P) -> io::Result<Option<(String, String)>> {
@@ -0,0 +1,274 @@
+use crate::generator::{executable_name, walk_source};
+use crate::snap::{File, Slot};
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+/// The plugs required by a daemon
+pub const DAEMON_PLUGS: [&str; 1] = ["network-bind"];
+
+/// A service definition found in the source (systemd unit, Procfile entry)
+#[derive(Debug, PartialEq)]
+pub struct Service {
+    /// The name of the executable started by the service
+    pub executable: String,
+    /// The snap daemon type (simple, forking, notify, ...)
+    pub daemon: String,
+    /// The snap restart condition, if any
+    pub restart_condition: Option<String>,
+    /// The D-Bus name acquired by the service, if any
+    pub bus_name: Option<String>,
+}
+
+/// The services shipped by a source
+#[derive(Debug, Default, PartialEq)]
+pub struct Services {
+    pub services: Vec<Service>,
+}
+
+impl Services {
+    /// Scan the given source tree to find systemd units and Procfile.
+    pub fn find<P: AsRef<Path>>(source_path: P) -> Result<Services> {
+        let mut services = Services::default();
+
+        for entry in walk_source(&source_path) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let file_name = entry.file_name().to_string_lossy();
+            if file_name.ends_with(".service") {
+                log::debug!("Found systemd unit ({})", entry.path().display());
+                let content = fs::read_to_string(entry.path())?;
+                services.services.extend(parse_systemd_unit(&content));
+            } else if file_name == "Procfile" {
+                log::debug!("Found Procfile ({})", entry.path().display());
+                let content = fs::read_to_string(entry.path())?;
+                services.services.extend(parse_procfile(&content));
+            }
+        }
+
+        Ok(services)
+    }
+
+    /// Turn the snap apps described by a service into daemons. Graphical apps
+    /// (having the desktop plug) are never turned into daemons.
+    pub fn apply(&self, snap: &mut File) {
+        for (name, app) in snap.apps.iter_mut() {
+            if app.plugs.iter().flatten().any(|p| p == "desktop") {
+                continue;
+            }
+
+            if let Some(service) = self.services.iter().find(|s| s.executable == *name) {
+                log::debug!("Set app {} as {} daemon", name, service.daemon);
+                app.daemon = Some(service.daemon.clone());
+                app.restart_condition = service.restart_condition.clone();
+
+                // D-Bus activated daemons must own a bus name, exposed through a dbus slot
+                if service.daemon == "dbus" {
+                    match &service.bus_name {
+                        Some(bus_name) => {
+                            let slot_name = format!("{}-dbus", name);
+                            log::debug!("Set app {} bus-name to `{}`", name, bus_name);
+                            app.bus_name = Some(bus_name.clone());
+                            app.slots = Some(vec![slot_name.clone()]);
+                            snap.slots.insert(
+                                slot_name,
+                                Slot {
+                                    interface: "dbus".to_string(),
+                                    bus: Some("system".to_string()),
+                                    name: Some(bus_name.clone()),
+                                },
+                            );
+                        }
+                        None => {
+                            log::warn!(
+                                "No BusName set for dbus service {}, using a simple daemon",
+                                name
+                            );
+                            app.daemon = Some("simple".to_string());
+                        }
+                    }
+                }
+            }
+
+            if app.daemon.is_some() {
+                app.add_plugs(&DAEMON_PLUGS);
+            }
+        }
+    }
+}
+
+fn parse_systemd_unit(content: &str) -> Option<Service> {
+    let mut in_service = false;
+    let mut executable = None;
+    let mut daemon = "simple".to_string();
+    let mut restart_condition = None;
+    let mut bus_name = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_service = line == "[Service]";
+            continue;
+        }
+        if !in_service {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim();
+            match key.trim() {
+                "ExecStart" => {
+                    executable = executable_name(value.trim_start_matches(|c| "-@:+!".contains(c)))
+                }
+                "Type" => {
+                    daemon = match value {
+                        "forking" | "oneshot" | "notify" | "dbus" => value.to_string(),
+                        _ => "simple".to_string(),
+                    }
+                }
+                "BusName" => bus_name = Some(value.to_string()),
+                "Restart" => {
+                    restart_condition = match value {
+                        "no" => Some("never".to_string()),
+                        "always" | "on-success" | "on-failure" | "on-abnormal" | "on-abort"
+                        | "on-watchdog" => Some(value.to_string()),
+                        _ => None,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    executable.map(|executable| Service {
+        executable,
+        daemon,
+        restart_condition,
+        bus_name,
+    })
+}
+
+fn parse_procfile(content: &str) -> Vec<Service> {
+    content
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .filter_map(|l| l.split_once(':'))
+        .filter_map(|(_, command)| executable_name(command))
+        .map(|executable| Service {
+            executable,
+            daemon: "simple".to_string(),
+            restart_condition: None,
+            bus_name: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snap::App;
+
+    #[test]
+    fn test_parse_systemd_unit() {
+        let unit = "[Unit]\nDescription=Foo\n\n[Service]\nType=notify\nBusName=org.example.Foo\nExecStart=-/usr/bin/food --config /etc/foo.toml\nRestart=always\n\n[Install]\nWantedBy=multi-user.target\n";
+        assert_eq!(
+            parse_systemd_unit(unit),
+            Some(Service {
+                executable: "food".to_string(),
+                daemon: "notify".to_string(),
+                restart_condition: Some("always".to_string()),
+                bus_name: Some("org.example.Foo".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_procfile() {
+        let procfile =
+            "# processes\nweb: bin/server --port $PORT\nworker: env QUEUE=default worker\n";
+        let executables: Vec<String> = parse_procfile(procfile)
+            .into_iter()
+            .map(|s| s.executable)
+            .collect();
+        assert_eq!(executables, vec!["server", "worker"]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let services = Services {
+            services: vec![
+                Service {
+                    executable: "server".to_string(),
+                    daemon: "forking".to_string(),
+                    restart_condition: Some("on-failure".to_string()),
+                    bus_name: None,
+                },
+                Service {
+                    executable: "food".to_string(),
+                    daemon: "simple".to_string(),
+                    restart_condition: None,
+                    bus_name: None,
+                },
+                Service {
+                    executable: "busd".to_string(),
+                    daemon: "dbus".to_string(),
+                    restart_condition: None,
+                    bus_name: Some("org.example.Bus".to_string()),
+                },
+                Service {
+                    executable: "nonamed".to_string(),
+                    daemon: "dbus".to_string(),
+                    restart_condition: None,
+                    bus_name: None,
+                },
+            ],
+        };
+
+        let mut snap = File::new("foo");
+        snap.apps
+            .insert("server".to_string(), App::new("bin/server"));
+        snap.apps
+            .insert("containerd".to_string(), App::new("bin/containerd"));
+        snap.apps.insert("busd".to_string(), App::new("bin/busd"));
+        snap.apps
+            .insert("nonamed".to_string(), App::new("bin/nonamed"));
+        let mut gui = App::new("bin/food");
+        gui.add_plugs(&["desktop"]);
+        snap.apps.insert("food".to_string(), gui);
+        services.apply(&mut snap);
+
+        let server = snap.apps.get("server").expect("server is not present");
+        assert_eq!(server.daemon, Some("forking".to_string()));
+        assert_eq!(server.restart_condition, Some("on-failure".to_string()));
+        assert_eq!(server.plugs, Some(vec!["network-bind".to_string()]));
+
+        // apps without service are left untouched, whatever their name is
+        let containerd = snap
+            .apps
+            .get("containerd")
+            .expect("containerd is not present");
+        assert_eq!(containerd.daemon, None);
+        assert_eq!(containerd.plugs, None);
+        assert_eq!(snap.apps.get("food").and_then(|a| a.daemon.clone()), None);
+
+        let busd = snap.apps.get("busd").expect("busd is not present");
+        assert_eq!(busd.daemon, Some("dbus".to_string()));
+        assert_eq!(busd.bus_name, Some("org.example.Bus".to_string()));
+        assert_eq!(busd.slots, Some(vec!["busd-dbus".to_string()]));
+        assert_eq!(
+            snap.slots.get("busd-dbus"),
+            Some(&Slot {
+                interface: "dbus".to_string(),
+                bus: Some("system".to_string()),
+                name: Some("org.example.Bus".to_string()),
+            })
+        );
+
+        let nonamed = snap.apps.get("nonamed").expect("nonamed is not present");
+        assert_eq!(nonamed.daemon, Some("simple".to_string()));
+        assert_eq!(nonamed.bus_name, None);
+    }
+}
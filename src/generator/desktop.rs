@@ -0,0 +1,521 @@
+use crate::generator::{executable_name, walk_source};
+use crate::snap::{File, GUI_DIRECTORY};
+use crate::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The plugs required by a graphical application
+pub const DESKTOP_PLUGS: [&str; 4] = ["desktop", "desktop-legacy", "wayland", "x11"];
+
+const ICON_DIRECTORIES: [&str; 3] = ["assets", "icons", "data"];
+
+/// A desktop entry (.desktop file) found in the source
+#[derive(Debug, PartialEq)]
+pub struct DesktopEntry {
+    /// Path of the file, relative to the source root
+    pub path: String,
+    /// The name of the executable launched by this entry
+    pub exec: Option<String>,
+    /// The icon name used by this entry
+    pub icon: Option<String>,
+}
+
+/// An AppStream metadata file (*.metainfo.xml / *.appdata.xml) found in the source
+#[derive(Debug, PartialEq)]
+pub struct Metainfo {
+    /// Path of the file, relative to the source root
+    pub path: String,
+    /// The AppStream component id
+    pub id: Option<String>,
+}
+
+/// The graphical resources (desktop entries, AppStream metadata, icons) shipped by a source
+#[derive(Debug, Default, PartialEq)]
+pub struct GuiResources {
+    pub desktop_entries: Vec<DesktopEntry>,
+    pub metainfo: Option<Metainfo>,
+    pub icons: Vec<String>,
+}
+
+impl GuiResources {
+    /// Scan the given source tree to find its graphical resources.
+    pub fn find<P: AsRef<Path>>(source_path: P) -> Result<GuiResources> {
+        let mut resources = GuiResources::default();
+
+        for entry in walk_source(&source_path) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative_path = relative_path(&source_path, entry.path());
+            let file_name = entry.file_name().to_string_lossy();
+
+            if file_name.ends_with(".desktop") {
+                log::debug!("Found desktop entry ({})", relative_path);
+                let content = fs::read_to_string(entry.path())?;
+                resources.desktop_entries.push(DesktopEntry {
+                    path: relative_path,
+                    exec: desktop_entry_value(&content, "Exec").and_then(|v| executable_name(&v)),
+                    icon: desktop_entry_value(&content, "Icon"),
+                });
+            } else if file_name.ends_with(".metainfo.xml") || file_name.ends_with(".appdata.xml") {
+                if resources.metainfo.is_none() {
+                    log::debug!("Found AppStream metadata ({})", relative_path);
+                    let content = fs::read_to_string(entry.path())?;
+                    resources.metainfo = Some(Metainfo {
+                        path: relative_path,
+                        id: xml_element_value(&content, "id"),
+                    });
+                }
+            } else if (file_name.ends_with(".png") || file_name.ends_with(".svg"))
+                && is_in_icon_directory(&source_path, entry.path())
+            {
+                resources.icons.push(relative_path);
+            }
+        }
+
+        Ok(resources)
+    }
+
+    /// Find the icon of the snap: the one referenced by a desktop entry, or named after
+    /// the snap or its AppStream id. Icons are only looked up for graphical sources.
+    pub fn icon(&self, snap_name: &str) -> Option<String> {
+        if self.desktop_entries.is_empty() && self.metainfo.is_none() {
+            return None;
+        }
+
+        let stem = |path: &str| {
+            Path::new(path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+
+        let mut names: Vec<String> = self
+            .desktop_entries
+            .iter()
+            .filter_map(|e| e.icon.as_deref())
+            .map(stem)
+            .collect();
+        names.push(snap_name.to_string());
+        if let Some(id) = self.metainfo.as_ref().and_then(|m| m.id.as_ref()) {
+            names.push(id.clone());
+        }
+
+        for name in names {
+            // svg are preferred
+            let mut icons = self.icons.iter().filter(|i| stem(i) == name);
+            if let Some(icon) = icons
+                .clone()
+                .find(|i| i.ends_with(".svg"))
+                .or_else(|| icons.next())
+            {
+                return Some(icon.clone());
+            }
+        }
+
+        None
+    }
+
+    /// Find the desktop entry that launches given app.
+    pub fn desktop_entry(&self, app_name: &str, app_count: usize) -> Option<&DesktopEntry> {
+        let entry = self.desktop_entries.iter().find(|e| {
+            e.exec.as_deref() == Some(app_name)
+                || entry_name(&e.path).eq_ignore_ascii_case(app_name)
+        });
+
+        // If there's a single app and a single desktop file, they belongs together
+        if entry.is_none() && app_count == 1 && self.desktop_entries.len() == 1 {
+            return self.desktop_entries.first();
+        }
+
+        entry
+    }
+
+    /// Complete given snap with the graphical resources: copy icon and desktop files
+    /// of each graphical app into snap/gui, and set their common-id & plugs.
+    /// If AppStream metadata are available, the snap will adopt them.
+    pub fn apply<P: AsRef<Path>>(&self, source_path: P, snap: &mut File) -> Result<()> {
+        // the icon set by the generator takes precedence
+        let mut gui_icon = None;
+        if let Some(icon) = self.icon(&snap.name).filter(|_| snap.icon.is_none()) {
+            let extension = Path::new(&icon)
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let name = format!("{}.{}", snap.name, extension);
+
+            log::debug!("Copy snap icon `{}` as {}/{}", icon, GUI_DIRECTORY, name);
+            let content = fs::read(source_path.as_ref().join(&icon))?;
+            snap.gui_files.insert(name.clone(), content);
+            snap.icon = Some(format!("{}/{}", GUI_DIRECTORY, name));
+            gui_icon = Some(name);
+        }
+
+        if let Some(metainfo) = &self.metainfo {
+            adopt_metainfo(snap, &metainfo.path);
+        }
+
+        let app_count = snap.apps.len();
+        let metainfo_id = self.metainfo.as_ref().and_then(|m| m.id.clone());
+
+        for (name, app) in snap.apps.iter_mut() {
+            if let Some(entry) = self.desktop_entry(name, app_count) {
+                // snapcraft picks up snap/gui/<app>.desktop, which must launch the snap app
+                let desktop_name = format!("{}.desktop", name);
+                let command = if *name == snap.name {
+                    name.clone()
+                } else {
+                    format!("{}.{}", snap.name, name)
+                };
+
+                log::debug!(
+                    "Copy app {} desktop file `{}` as {}/{}",
+                    name,
+                    entry.path,
+                    GUI_DIRECTORY,
+                    desktop_name
+                );
+                let content = fs::read_to_string(source_path.as_ref().join(&entry.path))?;
+                let content = snap_desktop_entry(
+                    &content,
+                    entry.exec.as_deref(),
+                    &command,
+                    gui_icon.as_deref(),
+                );
+                snap.gui_files.insert(desktop_name, content.into_bytes());
+                app.add_plugs(&DESKTOP_PLUGS);
+
+                // graphical apps are not daemons, whatever their dependencies are
+                if app.daemon.take().is_some() {
+                    log::debug!("Unset app {} daemon (graphical app)", name);
+                }
+
+                // The AppStream id is shared by the desktop file
+                if let Some(id) = &metainfo_id {
+                    let desktop_id = Path::new(&entry.path)
+                        .file_name()
+                        .map(|f| f.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    if app_count == 1
+                        || desktop_id == *id
+                        || desktop_id == format!("{}.desktop", id)
+                    {
+                        log::debug!("Set app {} common-id to `{}`", name, id);
+                        app.common_id = Some(id.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Make the snap adopt its summary, description and version (those not set yet)
+/// from the AppStream metadata file, parsed by the main part.
+fn adopt_metainfo(snap: &mut File, metainfo_path: &str) {
+    let part_name = match snap.parts.keys().next() {
+        Some(name) => name.clone(),
+        None => return,
+    };
+
+    log::debug!(
+        "Adopting info from AppStream metadata ({}) using part {}",
+        metainfo_path,
+        part_name
+    );
+    if let Some(part) = snap.parts.get_mut(&part_name) {
+        part.parse_info = Some(vec![metainfo_path.to_string()]);
+    }
+    snap.adopt_info = Some(part_name);
+
+    for field in [&mut snap.summary, &mut snap.description, &mut snap.version] {
+        if field.as_deref() == Some("TODO") {
+            *field = None;
+        }
+    }
+}
+
+/// Point the Exec & Icon keys of given desktop entry to the snap app & icon.
+fn snap_desktop_entry(
+    content: &str,
+    exec: Option<&str>,
+    command: &str,
+    icon: Option<&str>,
+) -> String {
+    let mut in_main_group = false;
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_main_group = trimmed == "[Desktop Entry]";
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let key_value = trimmed.split_once('=').map(|(k, v)| (k.trim(), v.trim()));
+        match (key_value, icon) {
+            // the actions launching the same executable are redirected too
+            (Some(("Exec", value)), _)
+                if in_main_group
+                    || (exec.is_some() && executable_name(value).as_deref() == exec) =>
+            {
+                let arguments: Vec<&str> = value
+                    .split_whitespace()
+                    .skip_while(|arg| *arg == "env" || arg.contains('='))
+                    .skip(1)
+                    .collect();
+                lines.push(
+                    format!("Exec={} {}", command, arguments.join(" "))
+                        .trim_end()
+                        .to_string(),
+                );
+            }
+            (Some(("Icon", _)), Some(icon)) if in_main_group => {
+                lines.push(format!("Icon=${{SNAP}}/meta/gui/{}", icon));
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn is_in_icon_directory<P: AsRef<Path>>(source_path: P, path: &Path) -> bool {
+    path.strip_prefix(&source_path)
+        .map(|p| {
+            p.components()
+                .any(|c| ICON_DIRECTORIES.contains(&c.as_os_str().to_string_lossy().as_ref()))
+        })
+        .unwrap_or(false)
+}
+
+fn relative_path<P: AsRef<Path>>(source_path: P, path: &Path) -> String {
+    path.strip_prefix(&source_path)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+// the name of a desktop entry: org.example.Foo.desktop -> Foo
+fn entry_name(path: &str) -> String {
+    let file_name = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    file_name
+        .rsplit('.')
+        .next()
+        .unwrap_or(&file_name)
+        .to_string()
+}
+
+// extract the value of given key from the [Desktop Entry] group
+fn desktop_entry_value(content: &str, key: &str) -> Option<String> {
+    let mut in_main_group = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if in_main_group {
+            if let Some((k, v)) = line.split_once('=') {
+                if k.trim() == key {
+                    return Some(v.trim().to_string());
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// naive extraction of the first value of given element
+fn xml_element_value(content: &str, element: &str) -> Option<String> {
+    let start_tag = format!("<{}>", element);
+    let end_tag = format!("</{}>", element);
+
+    let start = content.find(&start_tag)? + start_tag.len();
+    let end = content[start..].find(&end_tag)?;
+
+    Some(content[start..start + end].trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snap::{App, Part};
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_find_gui_resources() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let path = tmp_dir.path();
+        fs::create_dir_all(path.join("data").join("icons")).expect("unable to create data/icons");
+        fs::create_dir_all(path.join("target")).expect("unable to create target");
+
+        fs::write(
+            path.join("data").join("org.example.Foo.desktop"),
+            "[Desktop Entry]\nName=Foo\nExec=env GDK_BACKEND=x11 /usr/bin/foo %U\nIcon=foo\n[Desktop Action New]\nExec=bar\n",
+        )
+        .expect("unable to write desktop file");
+        fs::write(
+            path.join("data").join("org.example.Foo.metainfo.xml"),
+            "<component type=\"desktop-application\">\n  <id>org.example.Foo</id>\n</component>",
+        )
+        .expect("unable to write metainfo file");
+        fs::write(path.join("data").join("icons").join("bar.png"), "")
+            .expect("unable to write bar.png");
+        fs::write(path.join("data").join("icons").join("foo.png"), "")
+            .expect("unable to write foo.png");
+        fs::write(path.join("target").join("foo.desktop"), "")
+            .expect("unable to write target/foo.desktop");
+
+        let resources = GuiResources::find(path).expect("unable to find gui resources");
+        assert_eq!(
+            resources.desktop_entries,
+            vec![DesktopEntry {
+                path: "data/org.example.Foo.desktop".to_string(),
+                exec: Some("foo".to_string()),
+                icon: Some("foo".to_string()),
+            }]
+        );
+        assert_eq!(
+            resources.metainfo,
+            Some(Metainfo {
+                path: "data/org.example.Foo.metainfo.xml".to_string(),
+                id: Some("org.example.Foo".to_string()),
+            })
+        );
+        assert_eq!(
+            resources.icon("foo"),
+            Some("data/icons/foo.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_icon() {
+        let mut resources = GuiResources {
+            desktop_entries: vec![],
+            metainfo: None,
+            icons: vec![
+                "assets/logo.svg".to_string(),
+                "assets/org.example.Foo.png".to_string(),
+                "assets/org.example.Foo.svg".to_string(),
+            ],
+        };
+        // not a graphical source
+        assert_eq!(resources.icon("foo"), None);
+
+        resources.metainfo = Some(Metainfo {
+            path: "data/org.example.Foo.metainfo.xml".to_string(),
+            id: Some("org.example.Foo".to_string()),
+        });
+        assert_eq!(
+            resources.icon("foo"),
+            Some("assets/org.example.Foo.svg".to_string())
+        );
+
+        // no icon named after the snap, the desktop Icon= or the AppStream id
+        resources.metainfo = None;
+        resources.desktop_entries.push(DesktopEntry {
+            path: "data/foo.desktop".to_string(),
+            exec: Some("foo".to_string()),
+            icon: Some("bar".to_string()),
+        });
+        assert_eq!(resources.icon("foo"), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let path = tmp_dir.path();
+        fs::create_dir_all(path.join("data").join("icons")).expect("unable to create data/icons");
+        fs::write(
+            path.join("data").join("org.example.Foo.desktop"),
+            "[Desktop Entry]\nName=Foo\nExec=env GDK_BACKEND=x11 /usr/bin/foo %U\nIcon=foo\n\n[Desktop Action New]\nExec=foo --new-window\n",
+        )
+        .expect("unable to write desktop file");
+        fs::write(
+            path.join("data").join("org.example.Foo.metainfo.xml"),
+            "<component type=\"desktop-application\">\n  <id>org.example.Foo</id>\n</component>",
+        )
+        .expect("unable to write metainfo file");
+        fs::write(path.join("data").join("icons").join("foo.svg"), "<svg/>")
+            .expect("unable to write foo.svg");
+        let resources = GuiResources::find(path).expect("unable to find gui resources");
+
+        let mut snap = File::new("foo");
+        snap.parts.insert("foo".to_string(), Part::new("rust"));
+        let mut foo = App::new("bin/foo");
+        foo.daemon = Some("simple".to_string());
+        snap.apps.insert("foo".to_string(), foo);
+        snap.apps
+            .insert("foo-cli".to_string(), App::new("bin/foo-cli"));
+        resources
+            .apply(path, &mut snap)
+            .expect("unable to apply gui resources");
+
+        assert_eq!(snap.icon, Some("snap/gui/foo.svg".to_string()));
+        assert_eq!(snap.adopt_info, Some("foo".to_string()));
+        assert_eq!(snap.version, None);
+        assert_eq!(snap.summary, None);
+        assert_eq!(snap.description, None);
+
+        let part = snap.parts.get("foo").expect("foo part is not present");
+        assert_eq!(
+            part.parse_info,
+            Some(vec!["data/org.example.Foo.metainfo.xml".to_string()])
+        );
+
+        let foo = snap.apps.get("foo").expect("foo is not present");
+        assert_eq!(foo.desktop, None);
+        assert_eq!(foo.common_id, Some("org.example.Foo".to_string()));
+        assert_eq!(foo.daemon, None);
+        assert_eq!(
+            foo.plugs,
+            Some(DESKTOP_PLUGS.iter().map(|p| p.to_string()).collect())
+        );
+
+        let foo_cli = snap.apps.get("foo-cli").expect("foo-cli is not present");
+        assert_eq!(foo_cli.plugs, None);
+
+        // the icon & desktop file are copied where snapcraft looks for them
+        snap.write_gui_files(path)
+            .expect("unable to write gui files");
+        assert_eq!(
+            fs::read_to_string(path.join("snap").join("gui").join("foo.svg"))
+                .expect("unable to read snap/gui/foo.svg"),
+            "<svg/>"
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("snap").join("gui").join("foo.desktop"))
+                .expect("unable to read snap/gui/foo.desktop"),
+            "[Desktop Entry]\nName=Foo\nExec=foo %U\nIcon=${SNAP}/meta/gui/foo.svg\n\n[Desktop Action New]\nExec=foo --new-window\n"
+        );
+        assert!(!path
+            .join("snap")
+            .join("gui")
+            .join("foo-cli.desktop")
+            .exists());
+
+        // the values set by the generator are kept
+        let mut snap = File::new("foo");
+        snap.parts.insert("foo".to_string(), Part::new("rust"));
+        snap.summary = Some("The foo tool".to_string());
+        snap.icon = Some("assets/logo.png".to_string());
+        resources
+            .apply(path, &mut snap)
+            .expect("unable to apply gui resources");
+        assert_eq!(snap.summary, Some("The foo tool".to_string()));
+        assert_eq!(snap.description, None);
+        assert_eq!(snap.icon, Some("assets/logo.png".to_string()));
+        assert!(snap.gui_files.is_empty());
+    }
+}
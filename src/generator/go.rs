@@ -1,62 +1,195 @@
-use crate::generator::{Generator, Provider};
+use crate::generator::licenses::DependencyLock;
+use crate::generator::{walk_source, Generator, Options, Provider};
 use crate::snap::{App, Part};
 use crate::Result;
+use goreleaser::{is_template, Build, Goreleaser, Snapcraft};
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
-use std::io::{BufRead, BufReader};
-use std::path::{Path, PathBuf};
-use std::{fs, io};
-use walkdir::WalkDir;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+pub use mod_file::ModFile;
+
+mod goreleaser;
+mod mod_file;
+
+/// The files setting ldflags at build time
+const LDFLAGS_FILES: [&str; 4] = [
+    "Makefile",
+    ".goreleaser.yml",
+    ".goreleaser.yaml",
+    "magefile.go",
+];
+
+/// The plugs needed by the apps depending on given modules
+const MODULE_PLUGS: [(&str, &[&str]); 14] = [
+    ("github.com/gin-gonic/gin", &["network", "network-bind"]),
+    ("github.com/go-chi/chi", &["network", "network-bind"]),
+    ("github.com/gofiber/fiber", &["network", "network-bind"]),
+    ("github.com/gorilla/mux", &["network", "network-bind"]),
+    ("github.com/gorilla/websocket", &["network"]),
+    ("github.com/labstack/echo", &["network", "network-bind"]),
+    ("github.com/go-redis/redis", &["network"]),
+    ("github.com/go-resty/resty", &["network"]),
+    ("github.com/go-sql-driver/mysql", &["network"]),
+    ("github.com/jackc/pgx", &["network"]),
+    ("github.com/lib/pq", &["network"]),
+    ("github.com/redis/go-redis", &["network"]),
+    ("go.mongodb.org/mongo-driver", &["network"]),
+    ("google.golang.org/grpc", &["network", "network-bind"]),
+];
+
+/// The go version shipped in the archive of the bases using the go-channel option
+const BASE_GO_VERSIONS: [(&str, &str); 2] = [("core18", "1.10"), ("core20", "1.13")];
+
+/// The operating systems (GOOS) other than linux
+const NON_LINUX_OS: [&str; 14] = [
+    "aix",
+    "android",
+    "darwin",
+    "dragonfly",
+    "freebsd",
+    "illumos",
+    "ios",
+    "js",
+    "netbsd",
+    "openbsd",
+    "plan9",
+    "solaris",
+    "wasip1",
+    "windows",
+];
+
+/// The C libraries used through cgo:
+/// (pkg-config name, linker name, build packages, stage packages)
+const CGO_LIBRARIES: [(&str, &str, &[&str], &[&str]); 12] = [
+    ("alsa", "asound", &["libasound2-dev"], &["libasound2"]),
+    ("gl", "GL", &["libgl1-mesa-dev"], &["libgl1"]),
+    (
+        "glib-2.0",
+        "glib-2.0",
+        &["libglib2.0-dev"],
+        &["libglib2.0-0"],
+    ),
+    ("gtk+-3.0", "gtk-3", &["libgtk-3-dev"], &["libgtk-3-0"]),
+    ("libpcap", "pcap", &["libpcap-dev"], &["libpcap0.8"]),
+    ("libsystemd", "systemd", &["libsystemd-dev"], &[]),
+    (
+        "libusb-1.0",
+        "usb-1.0",
+        &["libusb-1.0-0-dev"],
+        &["libusb-1.0-0"],
+    ),
+    ("libzmq", "zmq", &["libzmq3-dev"], &["libzmq5"]),
+    ("openssl", "ssl", &["libssl-dev"], &[]),
+    ("sqlite3", "sqlite3", &["libsqlite3-dev"], &["libsqlite3-0"]),
+    ("x11", "X11", &["libx11-dev"], &["libx11-6"]),
+    ("zlib", "z", &["zlib1g-dev"], &["zlib1g"]),
+];
+
+/// The libraries shipped by libc6
+const LIBC_LIBRARIES: [&str; 5] = ["c", "dl", "m", "pthread", "rt"];
+
+/// The modules using cgo, and the native libraries they depend on:
+/// (module, build packages, stage packages)
+const CGO_MODULES: [(&str, &[&str], &[&str]); 8] = [
+    (
+        "fyne.io/fyne",
+        &["libgl1-mesa-dev", "xorg-dev"],
+        &["libgl1"],
+    ),
+    (
+        "github.com/go-gl/glfw",
+        &["libgl1-mesa-dev", "xorg-dev"],
+        &["libgl1"],
+    ),
+    (
+        "github.com/google/gopacket",
+        &["libpcap-dev"],
+        &["libpcap0.8"],
+    ),
+    ("github.com/gotk3/gotk3", &["libgtk-3-dev"], &["libgtk-3-0"]),
+    (
+        "github.com/hajimehoshi/oto",
+        &["libasound2-dev"],
+        &["libasound2"],
+    ),
+    ("github.com/mattn/go-sqlite3", &[], &[]),
+    ("github.com/shirou/gopsutil", &[], &[]),
+    (
+        "github.com/veandco/go-sdl2",
+        &["libsdl2-dev"],
+        &["libsdl2-2.0-0"],
+    ),
+];
 
 pub struct GoGenerator {
-    mod_file: ModFile,
+    modules: Vec<Module>,
+    goreleaser: Option<Goreleaser>,
     source_path: PathBuf,
     source_name: String,
 }
 
-pub struct GoProvider {}
-
-// the go.mod file
-#[derive(Default)]
-struct ModFile {
-    pub go_version: String,
-    pub import_path: String,
+/// A go module of the source
+struct Module {
+    /// The module directory, relative to the source
+    path: PathBuf,
+    mod_file: ModFile,
+    /// The C libraries used through cgo, if cgo is used
+    cgo_libraries: Option<Vec<String>>,
+    /// The variable holding the version, to be set using ldflags (i.e `main.version`)
+    version_variable: Option<String>,
 }
 
-impl ModFile {
-    fn load<P: AsRef<Path>>(path: P) -> Result<ModFile> {
-        let mut mod_file = ModFile::default();
+pub struct GoProvider {}
 
-        for line in read_lines(&path)? {
-            if line.starts_with("module") {
-                mod_file.import_path = line.replace("module ", "");
-            }
-            if line.starts_with("go") {
-                mod_file.go_version = line.replace("go ", "");
-            }
+impl Provider<GoGenerator> for GoProvider {
+    fn provide<P: AsRef<Path>>(source_path: P, options: &Options) -> Result<GoGenerator> {
+        let mut modules = Vec::new();
+        for path in find_modules(&source_path)? {
+            log::debug!("Found go module ({})", path.display());
+            let module_path = source_path.as_ref().join(&path);
+            modules.push(Module {
+                mod_file: ModFile::load(module_path.join("go.mod"))?,
+                cgo_libraries: find_cgo_libraries(&module_path)?,
+                version_variable: find_version_variable(&module_path, &source_path)?,
+                path,
+            });
         }
 
-        Ok(mod_file)
-    }
-}
-
-impl Provider<GoGenerator> for GoProvider {
-    fn provide<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<GoGenerator> {
-        let mod_file = ModFile::load(source_path.as_ref().join("go.mod"))?;
         Ok(GoGenerator {
-            mod_file,
+            modules,
+            goreleaser: Goreleaser::load(&source_path)?,
             source_path: source_path.as_ref().to_path_buf(),
-            source_name: source_name.to_string(),
+            source_name: options.source_name.clone(),
         })
     }
 
     fn can_provide<P: AsRef<Path>>(source_path: P) -> bool {
         source_path.as_ref().join("go.mod").exists()
+            || source_path.as_ref().join("go.work").exists()
+            // multi-modules repositories have their modules in top-level directories
+            || fs::read_dir(&source_path)
+                .map(|entries| {
+                    entries
+                        .filter_map(|e| e.ok())
+                        .any(|e| e.path().join("go.mod").is_file())
+                })
+                .unwrap_or(false)
     }
 }
 
 impl Generator for GoGenerator {
+    fn base(&self) -> Result<Option<String>> {
+        Ok(self.snapcraft().and_then(|s| s.base.clone()))
+    }
+
     fn name(&self) -> Result<Option<String>> {
-        Ok(None)
+        Ok(self
+            .snapcraft()
+            .and_then(|s| s.name.clone())
+            .or_else(|| self.goreleaser.as_ref()?.project_name.clone())
+            .filter(|n| !is_template(n)))
     }
 
     fn version(&self) -> Result<Option<String>> {
@@ -64,76 +197,781 @@ impl Generator for GoGenerator {
     }
 
     fn summary(&self) -> Result<Option<String>> {
-        Ok(None)
+        Ok(self
+            .snapcraft()
+            .and_then(|s| s.summary.clone())
+            .filter(|v| !is_template(v)))
     }
 
     fn description(&self) -> Result<Option<String>> {
-        Ok(None)
+        Ok(self
+            .snapcraft()
+            .and_then(|s| s.description.clone())
+            .filter(|v| !is_template(v)))
     }
 
     fn license(&self) -> Result<Option<String>> {
+        Ok(self.snapcraft().and_then(|s| s.license.clone()))
+    }
+
+    fn icon(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn website(&self) -> Result<Option<String>> {
         Ok(None)
     }
 
+    fn source_code(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn issues(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn contact(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn grade(&self) -> Result<Option<String>> {
+        Ok(self
+            .snapcraft()
+            .and_then(|s| s.grade.clone())
+            .filter(|v| !is_template(v)))
+    }
+
+    fn confinement(&self) -> Result<Option<String>> {
+        Ok(self.snapcraft().and_then(|s| s.confinement.clone()))
+    }
+
     fn parts(&self) -> Result<BTreeMap<String, Part>> {
         let mut parts = BTreeMap::default();
-        parts.insert(
-            self.source_name.clone(),
-            Part {
-                plugin: "go".to_string(),
-                source: ".".to_string(),
-                build_packages: Some(vec!["gcc".to_string(), "libc6-dev".to_string()]),
-                stage_packages: None,
-                go_import_path: Some(self.mod_file.import_path.clone()),
-                python_version: None,
-            },
-        );
+        let base = self.base()?.unwrap_or_else(|| "core18".to_string());
+
+        for module in &self.modules {
+            // use one part per module when there are several of them
+            let name = if self.modules.len() == 1 {
+                self.source_name.clone()
+            } else {
+                module.name(&self.source_name)
+            };
+            // goreleaser builds the root module
+            let builds: Vec<&Build> = match &self.goreleaser {
+                Some(goreleaser) if module.path.as_os_str().is_empty() => {
+                    goreleaser.linux_builds().collect()
+                }
+                _ => Vec::new(),
+            };
+
+            if builds.len() <= 1 {
+                parts.insert(name, module.part(&base, builds.first().copied()));
+                continue;
+            }
+
+            // use one part per build, each one staging only the executable it builds
+            // (the go plugin builds all the main packages)
+            let root_name = module.name(&self.source_name);
+            for build in builds {
+                let executable = build.main_directory().unwrap_or_else(|| root_name.clone());
+                if parts.contains_key(&executable) {
+                    log::warn!(
+                        "Skipping goreleaser build of {} (a part with the same name already exists)",
+                        executable
+                    );
+                    continue;
+                }
+
+                let mut part = module.part(&base, Some(build));
+                part.stage = Some(vec![format!("bin/{}", executable)]);
+                parts.insert(executable, part);
+            }
+        }
 
         Ok(parts)
     }
 
     fn apps(&self) -> Result<BTreeMap<String, App>> {
         let mut apps = BTreeMap::default();
-        let executables = find_executables(self.source_path.clone())?;
-        for executable in executables {
-            apps.insert(
-                executable.to_string(),
-                App {
-                    command: format!("bin/{}", executable).to_string(),
-                    plugs: None,
-                },
-            );
+
+        for module in &self.modules {
+            let module_path = self.source_path.join(&module.path);
+            let root_name = module.name(&self.source_name);
+            let executables = match self.goreleaser_executables(module, &root_name) {
+                Some(executables) => executables,
+                None => find_executables(&module_path, &root_name)?
+                    .into_iter()
+                    .map(|e| (e.clone(), e))
+                    .collect(),
+            };
+
+            let mut plugs: Vec<&str> = Vec::new();
+            for (dependency, dependency_plugs) in MODULE_PLUGS.iter() {
+                if module.depends_on(dependency) {
+                    log::debug!(
+                        "Adding {:?} plugs as required by {}",
+                        dependency_plugs,
+                        dependency
+                    );
+                    plugs.extend(dependency_plugs.iter());
+                }
+            }
+
+            for (name, executable) in executables {
+                if apps.contains_key(&name) {
+                    log::warn!(
+                        "Skipping {} of module {} (an app with the same name already exists)",
+                        name,
+                        module.mod_file.import_path
+                    );
+                    continue;
+                }
+
+                let mut app = App::new(&format!("bin/{}", executable));
+                if !plugs.is_empty() {
+                    app.add_plugs(&plugs);
+                }
+
+                // apply the goreleaser snapcraft app settings
+                if let Some(snapcraft_app) = self.snapcraft().and_then(|s| s.apps.get(&name)) {
+                    let snapcraft_plugs: Vec<&str> =
+                        snapcraft_app.plugs.iter().map(|p| p.as_str()).collect();
+                    if !snapcraft_plugs.is_empty() {
+                        app.add_plugs(&snapcraft_plugs);
+                    }
+                    app.daemon = snapcraft_app.daemon.clone();
+                }
+
+                apps.insert(name, app);
+            }
         }
 
         Ok(apps)
     }
+
+    fn lockfiles(&self) -> Result<Vec<DependencyLock>> {
+        Ok(self
+            .modules
+            .iter()
+            .map(|m| self.source_path.join(&m.path).join("go.sum"))
+            .filter(|path| path.is_file())
+            .map(|path| DependencyLock {
+                path,
+                linked_packages: None,
+            })
+            .collect())
+    }
 }
 
-fn find_executables<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
-    let mut executables: Vec<String> = Vec::new();
+impl GoGenerator {
+    fn snapcraft(&self) -> Option<&Snapcraft> {
+        self.goreleaser.as_ref().and_then(|g| g.snapcraft())
+    }
 
-    for entry in WalkDir::new(&path) {
-        let entry = entry?;
+    /// The executables (app name, executable name) built by goreleaser for given module,
+    /// if it is built by goreleaser. The go plugin names the executables after their main
+    /// package directory, while goreleaser may rename them.
+    fn goreleaser_executables(
+        &self,
+        module: &Module,
+        root_name: &str,
+    ) -> Option<Vec<(String, String)>> {
+        if !module.path.as_os_str().is_empty() {
+            return None;
+        }
+        let goreleaser = self.goreleaser.as_ref()?;
+        let project_name = goreleaser
+            .project_name
+            .clone()
+            .filter(|n| !is_template(n))
+            .unwrap_or_else(|| root_name.to_string());
+
+        let executables: Vec<(String, String)> = goreleaser
+            .linux_builds()
+            .map(|build| {
+                let executable = build
+                    .main_directory()
+                    .unwrap_or_else(|| root_name.to_string());
+                let name = build
+                    .binary
+                    .clone()
+                    .filter(|b| !is_template(b))
+                    .unwrap_or_else(|| project_name.clone());
+                log::debug!(
+                    "Found goreleaser executable (name: {}, package: {})",
+                    name,
+                    executable
+                );
+                (name, executable)
+            })
+            .collect();
+
+        Some(executables).filter(|e| !e.is_empty())
+    }
+}
+
+impl Module {
+    /// The module name, used to name its part and its root executable
+    fn name(&self, source_name: &str) -> String {
+        module_name(&self.mod_file.import_path)
+            .or_else(|| self.path.file_name().and_then(|n| n.to_str()))
+            .unwrap_or(source_name)
+            .to_string()
+    }
 
-        if let Some(filename) = entry.file_name().to_str() {
-            if filename.ends_with(".go") {
-                let content = fs::read_to_string(entry.path())?;
-                if content.contains("package main") && content.contains("func main()") {
-                    let executable_name = filename.replace(".go", "");
-                    log::debug!("Found executable (name: {})", executable_name);
-                    executables.push(executable_name);
+    /// The part building the module, using given goreleaser build settings if any
+    fn part(&self, base: &str, build: Option<&Build>) -> Part {
+        let mut cgo_used = self.cgo_libraries.is_some();
+        let mut build_packages = vec!["gcc".to_string(), "libc6-dev".to_string()];
+        let mut stage_packages: Vec<String> = Vec::new();
+        let mut add_packages = |build: &[&str], stage: &[&str]| {
+            for package in build.iter() {
+                if !build_packages.iter().any(|p| p == package) {
+                    build_packages.push(package.to_string());
                 }
             }
+            for package in stage.iter() {
+                if !stage_packages.iter().any(|p| p == package) {
+                    stage_packages.push(package.to_string());
+                }
+            }
+        };
+
+        for (module, build, stage) in CGO_MODULES.iter() {
+            if self.depends_on(module) {
+                log::debug!(
+                    "Adding {:?} build packages and {:?} stage packages as required by {}",
+                    build,
+                    stage,
+                    module
+                );
+                cgo_used = true;
+                add_packages(build, stage);
+            }
+        }
+
+        for library in self
+            .cgo_libraries
+            .iter()
+            .flatten()
+            .filter(|l| !LIBC_LIBRARIES.contains(&l.as_str()))
+        {
+            match CGO_LIBRARIES
+                .iter()
+                .find(|(pkg_config, linker, _, _)| pkg_config == library || linker == library)
+            {
+                Some((_, _, build, stage)) => {
+                    log::debug!(
+                        "Adding {:?} build packages and {:?} stage packages as required by {}",
+                        build,
+                        stage,
+                        library
+                    );
+                    add_packages(build, stage);
+                }
+                None => log::warn!("Unknown C library {} used through cgo", library),
+            }
+        }
+
+        // the build environment & GOFLAGS, starting with the goreleaser build ones
+        let mut environment: Vec<(String, String)> = build
+            .map(|b| {
+                b.env
+                    .iter()
+                    .filter_map(|e| e.split_once('='))
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut goflags: Vec<String> = Vec::new();
+        if let Some(build) = build {
+            let tags = build.tags.to_vec();
+            if !tags.is_empty() {
+                goflags.push(format!("-tags={}", tags.join(",")));
+            }
+            goflags.extend(
+                build
+                    .flags
+                    .to_vec()
+                    .into_iter()
+                    .filter(|f| !f.contains(' ') && !is_template(f)),
+            );
+        }
+
+        if environment
+            .iter()
+            .any(|(k, v)| k == "CGO_ENABLED" && v == "1")
+        {
+            cgo_used = true;
+        }
+
+        let mut part = if cgo_used {
+            Part {
+                build_packages: Some(build_packages),
+                stage_packages: Some(stage_packages).filter(|p| !p.is_empty()),
+                ..Part::new("go")
+            }
+        } else {
+            // no need for a C toolchain, build static binaries
+            log::debug!("cgo is not used, disabling it");
+            if !environment.iter().any(|(k, _)| k == "CGO_ENABLED") {
+                environment.insert(0, ("CGO_ENABLED".to_string(), "0".to_string()));
+            }
+            Part::new("go")
+        };
+
+        // the go-importpath option is only supported (and required) by the core18 plugin
+        if base == "core18" {
+            part.go_import_path = Some(self.mod_file.import_path.clone());
+        }
+        part.source_subdir =
+            Some(self.path.to_string_lossy().to_string()).filter(|p| !p.is_empty());
+
+        // Inject the snap version into the binaries. GOFLAGS entries can't contain spaces
+        // so only a single variable can be set this way.
+        let version_variable = build
+            .and_then(|b| {
+                ldflags_variables(&b.ldflags.to_vec().join(" "))
+                    .into_iter()
+                    .find(|v| v.to_lowercase().ends_with(".version"))
+            })
+            .or_else(|| self.version_variable.clone());
+        if let Some(variable) = version_variable {
+            let project_version = if base == "core18" || base == "core20" {
+                "$SNAPCRAFT_PROJECT_VERSION"
+            } else {
+                "$CRAFT_PROJECT_VERSION"
+            };
+            goflags.push(format!("-ldflags=-X={}={}", variable, project_version));
+        }
+
+        if !goflags.is_empty() {
+            environment.push(("GOFLAGS".to_string(), goflags.join(" ")));
+        }
+        if !environment.is_empty() {
+            part.build_environment = Some(
+                environment
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let mut variable = BTreeMap::new();
+                        variable.insert(k, v);
+                        variable
+                    })
+                    .collect(),
+            );
+        }
+
+        // Install the go toolchain required by the module
+        if let Some(go_version) = self.go_version() {
+            let channel = format!("{}/stable", go_version);
+            // the go-channel option is only supported by the core18 and core20 plugins,
+            // the core22+ ones use the go snap declared as build snap
+            if base == "core18" || base == "core20" {
+                if let Some((_, base_version)) = BASE_GO_VERSIONS.iter().find(|(b, _)| *b == base) {
+                    if compare_versions(base_version, &go_version) == Ordering::Less {
+                        log::warn!(
+                            "The {} default go ({}) is older than the required one ({}), using the {} go channel",
+                            base,
+                            base_version,
+                            go_version,
+                            channel
+                        );
+                    }
+                }
+                part.go_channel = Some(channel);
+            } else {
+                part.build_snaps = Some(vec![format!("go/{}", channel)]);
+            }
+        }
+
+        part
+    }
+
+    /// The go version (major.minor) required by the module: the toolchain directive, or
+    /// the go one
+    fn go_version(&self) -> Option<String> {
+        let version = match &self.mod_file.toolchain {
+            Some(toolchain) => toolchain.trim_start_matches("go"),
+            None => self.mod_file.go_version.as_str(),
+        };
+
+        let version: Vec<&str> = version
+            .split(|c: char| !c.is_ascii_digit())
+            .take(2)
+            .collect();
+        if version.len() == 2 && version.iter().all(|v| !v.is_empty()) {
+            Some(version.join("."))
+        } else {
+            None
+        }
+    }
+
+    // check if the module requires given module (or one of its major versions / packages)
+    fn depends_on(&self, module: &str) -> bool {
+        self.mod_file.dependencies().iter().any(|dependency| {
+            *dependency == module
+                || dependency
+                    .strip_prefix(module)
+                    .map(|rest| rest.starts_with('/'))
+                    .unwrap_or(false)
+        })
+    }
+}
+
+/// Find the variable holding the version of the module executables: the one set using
+/// `-X` ldflags by the Makefile or the goreleaser configuration, or else a package-level
+/// `version` variable declared in a main package.
+fn find_version_variable<P: AsRef<Path>, S: AsRef<Path>>(
+    module_path: P,
+    source_path: S,
+) -> Result<Option<String>> {
+    for directory in [module_path.as_ref(), source_path.as_ref()].iter() {
+        for file in LDFLAGS_FILES.iter() {
+            let path = directory.join(file);
+            if !path.is_file() {
+                continue;
+            }
+
+            if let Some(variable) = ldflags_variables(&fs::read_to_string(&path)?)
+                .into_iter()
+                .find(|v| v.to_lowercase().ends_with(".version"))
+            {
+                log::debug!(
+                    "Found version variable {} (set by {})",
+                    variable,
+                    path.display()
+                );
+                return Ok(Some(variable));
+            }
+        }
+    }
+
+    for (relative_path, content) in go_sources(&module_path)? {
+        if package_name(&content) != Some("main") {
+            continue;
+        }
+
+        if let Some(name) = version_declaration(&content) {
+            log::debug!(
+                "Found version variable main.{} ({})",
+                name,
+                relative_path.display()
+            );
+            return Ok(Some(format!("main.{}", name)));
+        }
+    }
+
+    Ok(None)
+}
+
+// the variables set using -X ldflags (i.e `-X main.version=$(VERSION)` -> main.version)
+fn ldflags_variables(content: &str) -> Vec<String> {
+    let tokens: Vec<&str> = content
+        .split(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '`')
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let mut variables = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let assignment = match *token {
+            "-X" => tokens.get(i + 1).copied(),
+            token => token.strip_prefix("-X="),
+        };
+        if let Some((variable, _)) = assignment.and_then(|a| a.split_once('=')) {
+            if variable.contains('.') && !variables.iter().any(|v| v == variable) {
+                variables.push(variable.to_string());
+            }
+        }
+    }
+
+    variables
+}
+
+// the name of a package-level version string variable (i.e `var version = "dev"`)
+fn version_declaration(content: &str) -> Option<&str> {
+    let mut in_var_block = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        let declaration = if in_var_block {
+            if line.starts_with(')') {
+                in_var_block = false;
+                continue;
+            }
+            line
+        } else if line == "var (" {
+            in_var_block = true;
+            continue;
+        } else if let Some(declaration) = line.strip_prefix("var ") {
+            declaration
+        } else {
+            continue;
+        };
+
+        let mut words = declaration.split_whitespace();
+        if let (Some(name), Some(next)) = (words.next(), words.next()) {
+            // only string variables can be set using -X
+            if (name == "version" || name == "Version")
+                && (next == "string"
+                    || (next == "=" && words.next().map(|v| v.starts_with('"')) == Some(true)))
+            {
+                return Some(name);
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the directories (relative to the source) of the go modules: the ones used
+/// by the go.work workspace if any, or else the root module. Multi-modules repositories
+/// without root module have all their directories having a go.mod as modules.
+fn find_modules<P: AsRef<Path>>(source_path: P) -> Result<Vec<PathBuf>> {
+    let go_work = source_path.as_ref().join("go.work");
+    if go_work.exists() {
+        log::debug!("Found go workspace ({})", go_work.display());
+        return Ok(ModFile::load(go_work)?
+            .uses
+            .iter()
+            .map(|dir| {
+                Path::new(dir)
+                    .components()
+                    .filter(|c| *c != Component::CurDir)
+                    .collect()
+            })
+            .collect());
+    }
+
+    // the nested modules (i.e examples, tools) of a root module are not part of it
+    if source_path.as_ref().join("go.mod").exists() {
+        return Ok(vec![PathBuf::new()]);
+    }
+
+    let mut modules = Vec::new();
+    for entry in walk_source(&source_path) {
+        let entry = entry?;
+        if entry.file_type().is_file() && entry.file_name() == "go.mod" {
+            let relative_path = entry.path().strip_prefix(&source_path)?;
+            if !relative_path
+                .components()
+                .any(|c| c.as_os_str() == "testdata")
+            {
+                modules.push(
+                    relative_path
+                        .parent()
+                        .unwrap_or_else(|| Path::new(""))
+                        .to_path_buf(),
+                );
+            }
+        }
+    }
+    modules.sort();
+
+    Ok(modules)
+}
+
+/// Find the main packages of the module: each directory containing a main package
+/// is installed as an executable named after it (or after the module for the root one).
+fn find_executables<P: AsRef<Path>>(path: P, root_name: &str) -> Result<Vec<String>> {
+    // the main packages directories, and whether they declare the main function
+    let mut main_packages: BTreeMap<PathBuf, bool> = BTreeMap::new();
+
+    for (relative_path, content) in go_sources(path)? {
+        if package_name(&content) == Some("main") {
+            let directory = relative_path.parent().unwrap_or_else(|| Path::new(""));
+            let has_main = main_packages.entry(directory.to_path_buf()).or_default();
+            *has_main |= content.contains("func main()");
         }
     }
 
+    let mut executables: Vec<String> = Vec::new();
+    for (directory, _) in main_packages.iter().filter(|(_, has_main)| **has_main) {
+        let executable_name = match directory.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => root_name.to_string(),
+        };
+
+        if executables.contains(&executable_name) {
+            log::warn!(
+                "Skipping {} (an executable named {} already exists)",
+                directory.display(),
+                executable_name
+            );
+            continue;
+        }
+
+        log::debug!(
+            "Found executable (name: {}, package: {})",
+            executable_name,
+            directory.display()
+        );
+        executables.push(executable_name);
+    }
+
     Ok(executables)
 }
 
-fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<Vec<String>> {
-    let file = fs::File::open(filename)?;
-    let reader = BufReader::new(file);
-    Ok(reader.lines().filter_map(io::Result::ok).collect())
+/// Find the C libraries (pkg-config names or linker flags) used through cgo by the module
+/// sources, returns None if cgo is not used.
+fn find_cgo_libraries<P: AsRef<Path>>(path: P) -> Result<Option<Vec<String>>> {
+    let mut cgo_used = false;
+    let mut libraries: Vec<String> = Vec::new();
+
+    for (relative_path, content) in go_sources(path)? {
+        if !content
+            .lines()
+            .any(|l| l.trim() == "import \"C\"" || l.trim() == "\"C\"")
+        {
+            continue;
+        }
+
+        log::debug!("Found cgo usage ({})", relative_path.display());
+        cgo_used = true;
+
+        // #cgo [GOOS...] pkg-config: gtk+-3.0 / #cgo LDFLAGS: -lsqlite3
+        for directive in content.lines().filter_map(|l| {
+            l.trim()
+                .trim_start_matches("//")
+                .trim()
+                .strip_prefix("#cgo ")
+        }) {
+            let (flags, values) = match directive.split_once(':') {
+                Some(directive) => directive,
+                None => continue,
+            };
+            let found: Vec<&str> = match flags.split_whitespace().last() {
+                Some("pkg-config") => values.split_whitespace().collect(),
+                Some("LDFLAGS") => values
+                    .split_whitespace()
+                    .filter_map(|f| f.strip_prefix("-l"))
+                    .collect(),
+                _ => continue,
+            };
+            for library in found {
+                if !libraries.iter().any(|l| l == library) {
+                    libraries.push(library.to_string());
+                }
+            }
+        }
+    }
+
+    Ok(Some(libraries).filter(|_| cgo_used))
+}
+
+/// The go source files (relative path & content) built for linux, skipping tests,
+/// testdata and files excluded by their build constraints.
+fn go_sources<P: AsRef<Path>>(path: P) -> Result<Vec<(PathBuf, String)>> {
+    let mut sources = Vec::new();
+    let mut nested_modules: Vec<PathBuf> = Vec::new();
+
+    for entry in walk_source(&path) {
+        let entry = entry?;
+        let relative_path = entry.path().strip_prefix(&path)?;
+
+        // nested modules sources belong to them
+        if nested_modules.iter().any(|m| relative_path.starts_with(m)) {
+            continue;
+        }
+        if entry.depth() > 0 && entry.file_type().is_dir() && entry.path().join("go.mod").exists() {
+            nested_modules.push(relative_path.to_path_buf());
+            continue;
+        }
+
+        // go ignores testdata directories and those starting with _
+        if relative_path.components().any(|c| {
+            let c = c.as_os_str().to_string_lossy();
+            c == "testdata" || c.starts_with('_')
+        }) {
+            continue;
+        }
+
+        let filename = entry.file_name().to_string_lossy();
+        if !entry.file_type().is_file()
+            || !filename.ends_with(".go")
+            || filename.ends_with("_test.go")
+            || excluded_by_file_name(&filename)
+        {
+            continue;
+        }
+
+        let content = fs::read_to_string(entry.path())?;
+        if excluded_by_build_constraint(&content) {
+            log::debug!("Skipping {} (build constraint)", entry.path().display());
+            continue;
+        }
+
+        sources.push((relative_path.to_path_buf(), content));
+    }
+
+    Ok(sources)
+}
+
+// compare two major.minor versions
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u32> { v.split('.').filter_map(|p| p.parse().ok()).collect() };
+    parse(a).cmp(&parse(b))
+}
+
+/// The executable name of the root package (i.e `github.com/foo/bar/v2` -> bar)
+fn module_name(import_path: &str) -> Option<&str> {
+    let mut elements = import_path.rsplit('/');
+    let last = elements.next().filter(|e| !e.is_empty())?;
+    let is_major_version =
+        last.len() > 1 && last.starts_with('v') && last[1..].chars().all(|c| c.is_ascii_digit());
+    if is_major_version {
+        elements.next()
+    } else {
+        Some(last)
+    }
+}
+
+// the package clause of a go source file
+fn package_name(content: &str) -> Option<&str> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("package "))
+        .and_then(|name| name.split_whitespace().next())
+}
+
+// files named *_GOOS.go or *_GOARCH.go are implicitly constrained, only keep the linux ones
+fn excluded_by_file_name(filename: &str) -> bool {
+    let stem = filename.trim_end_matches(".go");
+    stem.split('_')
+        .skip(1)
+        .any(|part| NON_LINUX_OS.contains(&part))
+}
+
+// whether the file build constraint (//go:build, // +build) excludes linux builds
+fn excluded_by_build_constraint(content: &str) -> bool {
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("package ") {
+            break;
+        }
+
+        let constraint = match line
+            .strip_prefix("//go:build")
+            .or_else(|| line.strip_prefix("// +build"))
+        {
+            Some(constraint) => constraint,
+            None => continue,
+        };
+
+        let tags: Vec<&str> = constraint
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '!'))
+            .filter(|t| !t.is_empty())
+            .collect();
+        if tags.contains(&"ignore") || tags.contains(&"!linux") || tags.contains(&"!unix") {
+            return true;
+        }
+
+        let targets_linux = tags.contains(&"linux") || tags.contains(&"unix");
+        if !targets_linux && tags.iter().any(|t| NON_LINUX_OS.contains(t)) {
+            return true;
+        }
+    }
+
+    false
 }
 
 #[cfg(test)]
@@ -156,15 +994,45 @@ mod tests {
     }
 
     #[test]
-    fn test_read_lines() {
+    fn test_parts_and_plugs() {
         let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
-        let mod_file = "module github.com/creekorful/trandoshan\ngo 1.14";
-        fs::write(tmp_dir.path().join("go.mod"), mod_file).expect("unable to write go.mod");
+        fs::write(
+            tmp_dir.path().join("main.go"),
+            "package main\nfunc main(){}",
+        )
+        .expect("unable to write main.go");
 
-        let lines = read_lines(tmp_dir.path().join("go.mod")).expect("unable to read go.mod");
+        let generator = GoGenerator {
+            goreleaser: None,
+            modules: vec![Module {
+                path: PathBuf::new(),
+                mod_file: ModFile::parse(
+                    "module foo\ngo 1.20\nrequire (\n    github.com/gin-gonic/gin v1.9.1\n    github.com/google/gopacket v1.1.19\n    github.com/lib/pqx v1.0.0\n)\n",
+                ),
+                cgo_libraries: None,
+                version_variable: None,
+            }],
+            source_path: tmp_dir.path().to_path_buf(),
+            source_name: "foo".to_string(),
+        };
+
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = parts.get("foo").expect("part foo is not present");
         assert_eq!(
-            lines,
-            vec!["module github.com/creekorful/trandoshan", "go 1.14"]
+            part.build_packages,
+            Some(vec![
+                "gcc".to_string(),
+                "libc6-dev".to_string(),
+                "libpcap-dev".to_string()
+            ])
+        );
+        assert_eq!(part.stage_packages, Some(vec!["libpcap0.8".to_string()]));
+
+        let apps = generator.apps().expect("unable to generate apps");
+        let app = apps.get("foo").expect("app foo is not present");
+        assert_eq!(
+            app.plugs,
+            Some(vec!["network".to_string(), "network-bind".to_string()])
         );
     }
 
@@ -194,11 +1062,334 @@ mod tests {
         )
         .expect("unable to write cmd/baz/baz.go");
 
-        let executables = find_executables(tmp_dir);
+        let executables = find_executables(tmp_dir.path(), "root");
         assert!(executables.is_ok());
 
         let mut executables = executables.unwrap();
         executables.sort();
         assert_eq!(executables, vec!["bar", "baz", "foo"]);
     }
+
+    #[test]
+    fn test_find_executables_by_package() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let files = [
+            ("main.go", "package main\nfunc main(){}"),
+            ("main_test.go", "package main\nfunc TestMain(){}"),
+            ("cmd/server/main.go", "package main\nfunc main(){}"),
+            ("cmd/server/routes.go", "package main\nfunc routes(){}"),
+            (
+                "cmd/gen/gen.go",
+                "//go:build ignore\n\npackage main\nfunc main(){}",
+            ),
+            ("cmd/win/main_windows.go", "package main\nfunc main(){}"),
+            (
+                "cmd/mac/main.go",
+                "//go:build darwin\npackage main\nfunc main(){}",
+            ),
+            (
+                "internal/lib/lib.go",
+                "package lib\n// func main() is not here",
+            ),
+            ("testdata/tool/main.go", "package main\nfunc main(){}"),
+        ];
+        for (path, content) in files.iter() {
+            let path = tmp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).expect("unable to create directory");
+            fs::write(&path, content).expect("unable to write go file");
+        }
+
+        let executables =
+            find_executables(tmp_dir.path(), "foo").expect("unable to find executables");
+        assert_eq!(executables, vec!["foo", "server"]);
+    }
+
+    #[test]
+    fn test_find_cgo_libraries() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join("main.go"),
+            "package main\nfunc main(){}",
+        )
+        .expect("unable to write main.go");
+        assert_eq!(
+            find_cgo_libraries(tmp_dir.path()).expect("unable to find cgo libraries"),
+            None
+        );
+
+        fs::write(
+            tmp_dir.path().join("gui.go"),
+            "package main\n\n// #cgo linux pkg-config: gtk+-3.0\n// #cgo LDFLAGS: -lsqlite3 -lm\n// #include <gtk/gtk.h>\nimport \"C\"\n",
+        )
+        .expect("unable to write gui.go");
+        assert_eq!(
+            find_cgo_libraries(tmp_dir.path()).expect("unable to find cgo libraries"),
+            Some(vec![
+                "gtk+-3.0".to_string(),
+                "sqlite3".to_string(),
+                "m".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parts_without_cgo() {
+        let generator = GoGenerator {
+            goreleaser: None,
+            modules: vec![Module {
+                path: PathBuf::new(),
+                mod_file: ModFile::parse("module foo\ngo 1.20\n"),
+                cgo_libraries: None,
+                version_variable: None,
+            }],
+            source_path: PathBuf::from("."),
+            source_name: "foo".to_string(),
+        };
+
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = parts.get("foo").expect("part foo is not present");
+        assert_eq!(part.build_packages, None);
+        let mut environment = BTreeMap::new();
+        environment.insert("CGO_ENABLED".to_string(), "0".to_string());
+        assert_eq!(part.build_environment, Some(vec![environment]));
+        assert_eq!(part.go_channel, Some("1.20/stable".to_string()));
+    }
+
+    #[test]
+    fn test_part_per_base() {
+        let module = Module {
+            path: PathBuf::new(),
+            mod_file: ModFile::parse("module github.com/foo/foo\ngo 1.21\n"),
+            cgo_libraries: None,
+            version_variable: None,
+        };
+
+        let part = module.part("core18", None);
+        assert_eq!(part.go_import_path, Some("github.com/foo/foo".to_string()));
+        assert_eq!(part.go_channel, Some("1.21/stable".to_string()));
+        assert_eq!(part.build_snaps, None);
+
+        let part = module.part("core20", None);
+        assert_eq!(part.go_import_path, None);
+        assert_eq!(part.go_channel, Some("1.21/stable".to_string()));
+        assert_eq!(part.build_snaps, None);
+
+        let part = module.part("core22", None);
+        assert_eq!(part.go_import_path, None);
+        assert_eq!(part.go_channel, None);
+        assert_eq!(part.build_snaps, Some(vec!["go/1.21/stable".to_string()]));
+    }
+
+    #[test]
+    fn test_go_version() {
+        let module = Module {
+            path: PathBuf::new(),
+            mod_file: ModFile::parse("module foo\ngo 1.21.0\ntoolchain go1.22.1\n"),
+            cgo_libraries: None,
+            version_variable: None,
+        };
+        assert_eq!(module.go_version(), Some("1.22".to_string()));
+
+        let module = Module {
+            mod_file: ModFile::parse("module foo\n"),
+            ..module
+        };
+        assert_eq!(module.go_version(), None);
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_multi_modules() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let files = [
+            ("go.work", "go 1.21\n\nuse (\n    ./api\n    ./tools\n)\n"),
+            ("api/go.mod", "module github.com/foo/api\n\ngo 1.21\n"),
+            ("api/main.go", "package main\nfunc main(){}"),
+            ("tools/go.mod", "module github.com/foo/tools\n\ngo 1.21\n"),
+            ("tools/cmd/gen/main.go", "package main\nfunc main(){}"),
+            ("tools/nested/go.mod", "module github.com/foo/nested\n"),
+            ("tools/nested/main.go", "package main\nfunc main(){}"),
+        ];
+        for (path, content) in files.iter() {
+            let path = tmp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).expect("unable to create directory");
+            fs::write(&path, content).expect("unable to write file");
+        }
+
+        assert_eq!(
+            find_modules(tmp_dir.path()).expect("unable to find modules"),
+            vec![PathBuf::from("api"), PathBuf::from("tools")]
+        );
+
+        let generator = GoProvider::provide(
+            tmp_dir.path(),
+            &Options {
+                source_name: "foo".to_string(),
+                ..Default::default()
+            },
+        )
+        .expect("unable to provide go generator");
+
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = parts.get("tools").expect("part tools is not present");
+        assert_eq!(part.source_subdir, Some("tools".to_string()));
+        assert_eq!(
+            part.go_import_path,
+            Some("github.com/foo/tools".to_string())
+        );
+        assert!(parts.contains_key("api"));
+
+        let apps = generator.apps().expect("unable to generate apps");
+        let apps: Vec<&String> = apps.keys().collect();
+        assert_eq!(apps, vec!["api", "gen"]);
+
+        // without workspace, only the root module is used if any
+        fs::remove_file(tmp_dir.path().join("go.work")).expect("unable to remove go.work");
+        assert_eq!(
+            find_modules(tmp_dir.path()).expect("unable to find modules"),
+            vec![
+                PathBuf::from("api"),
+                PathBuf::from("tools"),
+                PathBuf::from("tools/nested")
+            ]
+        );
+        fs::write(tmp_dir.path().join("go.mod"), "module github.com/foo/foo\n")
+            .expect("unable to write go.mod");
+        assert_eq!(
+            find_modules(tmp_dir.path()).expect("unable to find modules"),
+            vec![PathBuf::new()]
+        );
+    }
+
+    #[test]
+    fn test_find_version_variable() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join("main.go"),
+            "package main\n\nvar (\n\tcommit = \"none\"\n\tversion = \"dev\"\n)\n\nfunc main(){}",
+        )
+        .expect("unable to write main.go");
+        assert_eq!(
+            find_version_variable(tmp_dir.path(), tmp_dir.path())
+                .expect("unable to find version variable"),
+            Some("main.version".to_string())
+        );
+
+        fs::write(
+            tmp_dir.path().join("Makefile"),
+            "LDFLAGS := -X 'github.com/foo/bar/internal/build.Commit=$(COMMIT)' -X github.com/foo/bar/internal/build.Version=$(VERSION)\n\nbuild:\n\tgo build -ldflags \"$(LDFLAGS)\" ./...\n",
+        )
+        .expect("unable to write Makefile");
+        assert_eq!(
+            find_version_variable(tmp_dir.path(), tmp_dir.path())
+                .expect("unable to find version variable"),
+            Some("github.com/foo/bar/internal/build.Version".to_string())
+        );
+    }
+
+    #[test]
+    fn test_version_ldflags() {
+        let module = Module {
+            path: PathBuf::new(),
+            mod_file: ModFile::parse("module foo\n"),
+            cgo_libraries: None,
+            version_variable: Some("main.version".to_string()),
+        };
+
+        let part = module.part("core22", None);
+        let mut environment = BTreeMap::new();
+        environment.insert(
+            "GOFLAGS".to_string(),
+            "-ldflags=-X=main.version=$CRAFT_PROJECT_VERSION".to_string(),
+        );
+        assert_eq!(
+            part.build_environment.expect("build environment not set")[1],
+            environment
+        );
+    }
+
+    #[test]
+    fn test_goreleaser() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let files = [
+            ("go.mod", "module github.com/foo/bar\n\ngo 1.21\n"),
+            ("cmd/bar/main.go", "package main\nvar version string\nfunc main(){}"),
+            ("cmd/bar-server/main.go", "package main\nfunc main(){}"),
+            (
+                ".goreleaser.yaml",
+                "builds:\n  - id: cli\n    main: ./cmd/bar\n    binary: barctl\n    env: [CGO_ENABLED=0]\n    tags: [netgo]\n    ldflags: -X main.version={{.Version}}\n  - id: server\n    main: ./cmd/bar-server/main.go\n    env: [CGO_ENABLED=1]\n  - id: windows\n    main: ./cmd/bar-windows\n    goos: [windows]\nsnapcrafts:\n  - summary: The bar tool\n    description: \"{{ .ProjectName }} does bar things\"\n    grade: stable\n    confinement: strict\n    apps:\n      barctl:\n        plugs: [home]\n",
+            ),
+        ];
+        for (path, content) in files.iter() {
+            let path = tmp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).expect("unable to create directory");
+            fs::write(&path, content).expect("unable to write file");
+        }
+
+        let generator = GoProvider::provide(
+            tmp_dir.path(),
+            &Options {
+                source_name: "bar".to_string(),
+                ..Default::default()
+            },
+        )
+        .expect("unable to provide go generator");
+        assert_eq!(
+            generator.summary().unwrap(),
+            Some("The bar tool".to_string())
+        );
+        assert_eq!(generator.description().unwrap(), None);
+        assert_eq!(generator.grade().unwrap(), Some("stable".to_string()));
+        assert_eq!(generator.confinement().unwrap(), Some("strict".to_string()));
+
+        let apps = generator.apps().expect("unable to generate apps");
+        assert_eq!(apps.len(), 2);
+        let app = apps.get("barctl").expect("app barctl is not present");
+        assert_eq!(app.command, "bin/bar");
+        assert_eq!(app.plugs, Some(vec!["home".to_string()]));
+        let app = apps.get("bar").expect("app bar is not present");
+        assert_eq!(app.command, "bin/bar-server");
+
+        // one part per linux build
+        let parts = generator.parts().expect("unable to generate parts");
+        assert_eq!(
+            parts.keys().collect::<Vec<&String>>(),
+            vec!["bar", "bar-server"]
+        );
+        let part = parts
+            .get("bar-server")
+            .expect("part bar-server is not present");
+        assert_eq!(part.stage, Some(vec!["bin/bar-server".to_string()]));
+        assert_eq!(
+            part.build_packages,
+            Some(vec!["gcc".to_string(), "libc6-dev".to_string()])
+        );
+
+        let part = parts.get("bar").expect("part bar is not present");
+        assert_eq!(part.stage, Some(vec!["bin/bar".to_string()]));
+        let environment: Vec<(String, String)> = part
+            .build_environment
+            .clone()
+            .expect("build environment not set")
+            .into_iter()
+            .flat_map(|e| e.into_iter())
+            .collect();
+        assert_eq!(
+            environment,
+            vec![
+                ("CGO_ENABLED".to_string(), "0".to_string()),
+                (
+                    "GOFLAGS".to_string(),
+                    "-tags=netgo -ldflags=-X=main.version=$SNAPCRAFT_PROJECT_VERSION".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_name() {
+        assert_eq!(module_name("github.com/foo/bar"), Some("bar"));
+        assert_eq!(module_name("github.com/foo/bar/v2"), Some("bar"));
+        assert_eq!(module_name(""), None);
+    }
 }
@@ -0,0 +1,256 @@
+use crate::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const GORELEASER_FILES: [&str; 4] = [
+    ".goreleaser.yml",
+    ".goreleaser.yaml",
+    "goreleaser.yml",
+    "goreleaser.yaml",
+];
+
+/// A value that can be set either as a single string or as a list of them
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Strings {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Strings::Many(Vec::new())
+    }
+}
+
+impl Strings {
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            Strings::One(value) => vec![value.clone()],
+            Strings::Many(values) => values.clone(),
+        }
+    }
+}
+
+/// A boolean that can also be set using a template (i.e `{{ .Env.SKIP }}`)
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Bool {
+    Bool(bool),
+    Template(String),
+}
+
+impl Default for Bool {
+    fn default() -> Self {
+        Bool::Bool(false)
+    }
+}
+
+impl Bool {
+    /// The value of the boolean, templates that can't be resolved are considered false
+    pub fn is_true(&self) -> bool {
+        match self {
+            Bool::Bool(value) => *value,
+            Bool::Template(value) => value.trim() == "true",
+        }
+    }
+}
+
+/// The goreleaser configuration
+#[derive(Debug, Default, Deserialize)]
+pub struct Goreleaser {
+    #[serde(default)]
+    pub project_name: Option<String>,
+    #[serde(default)]
+    pub builds: Vec<Build>,
+    #[serde(default)]
+    pub snapcrafts: Vec<Snapcraft>,
+}
+
+/// A goreleaser build
+#[derive(Debug, Default, Deserialize)]
+pub struct Build {
+    #[serde(default)]
+    pub binary: Option<String>,
+    /// The main package path (i.e `./cmd/foo`)
+    #[serde(default)]
+    pub main: Option<String>,
+    #[serde(default)]
+    pub ldflags: Strings,
+    #[serde(default)]
+    pub flags: Strings,
+    /// The build environment (i.e `CGO_ENABLED=0`)
+    #[serde(default)]
+    pub env: Vec<String>,
+    #[serde(default)]
+    pub tags: Strings,
+    #[serde(default)]
+    pub goos: Vec<String>,
+    #[serde(default)]
+    pub skip: Bool,
+}
+
+/// A goreleaser snapcraft package
+#[derive(Debug, Default, Deserialize)]
+pub struct Snapcraft {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub grade: Option<String>,
+    #[serde(default)]
+    pub confinement: Option<String>,
+    #[serde(default)]
+    pub license: Option<String>,
+    #[serde(default)]
+    pub apps: BTreeMap<String, SnapcraftApp>,
+}
+
+/// An app of a goreleaser snapcraft package
+#[derive(Debug, Default, Deserialize)]
+pub struct SnapcraftApp {
+    #[serde(default)]
+    pub plugs: Vec<String>,
+    #[serde(default)]
+    pub daemon: Option<String>,
+}
+
+impl Goreleaser {
+    /// Load the goreleaser configuration located in given source, if any.
+    pub fn load<P: AsRef<Path>>(source_path: P) -> Result<Option<Goreleaser>> {
+        for file in GORELEASER_FILES.iter() {
+            let path = source_path.as_ref().join(file);
+            if path.is_file() {
+                log::debug!("Found goreleaser configuration ({})", path.display());
+                let content = fs::read_to_string(&path)?;
+                // goreleaser is only used as a hint, don't fail on unsupported configurations
+                return match serde_yaml::from_str(&content) {
+                    Ok(goreleaser) => Ok(Some(goreleaser)),
+                    Err(e) => {
+                        log::warn!(
+                            "Ignoring goreleaser configuration ({}): {}",
+                            path.display(),
+                            e
+                        );
+                        Ok(None)
+                    }
+                };
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The builds producing linux binaries
+    pub fn linux_builds(&self) -> impl Iterator<Item = &Build> {
+        self.builds.iter().filter(|b| {
+            !b.skip.is_true() && (b.goos.is_empty() || b.goos.iter().any(|os| os == "linux"))
+        })
+    }
+
+    /// The first snapcraft package
+    pub fn snapcraft(&self) -> Option<&Snapcraft> {
+        self.snapcrafts.first()
+    }
+}
+
+impl Build {
+    /// The name of the main package directory (None for the module root)
+    pub fn main_directory(&self) -> Option<String> {
+        let main = self.main.as_deref()?.trim_end_matches('/');
+        // main can also be a file (i.e `./cmd/foo/main.go`)
+        let main = if main.ends_with(".go") {
+            Path::new(main).parent()?
+        } else {
+            Path::new(main)
+        };
+        main.file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .filter(|f| f != "." && !f.is_empty())
+    }
+}
+
+// goreleaser values may use templates (i.e `{{ .ProjectName }}`) that can't be resolved
+pub fn is_template(value: &str) -> bool {
+    value.contains("{{")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_load() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join(".goreleaser.yml"),
+            "project_name: foo
+builds:
+  - binary: foo
+    main: ./cmd/foo/main.go
+    env:
+      - CGO_ENABLED=0
+    tags: netgo
+    ldflags:
+      - -s -w -X main.version={{.Version}}
+    goos: [linux, darwin]
+  - binary: foo-win
+    goos: [windows]
+  - binary: foo-snapshot
+    skip: true
+  - binary: foo-nightly
+    skip: \"{{ .Env.SKIP_NIGHTLY }}\"
+snapcrafts:
+  - summary: A foo tool
+    description: The foo tool does foo things
+    grade: stable
+    confinement: strict
+    apps:
+      foo:
+        plugs: [home, network]
+",
+        )
+        .expect("unable to write .goreleaser.yml");
+
+        let goreleaser = Goreleaser::load(tmp_dir.path())
+            .expect("unable to load goreleaser configuration")
+            .expect("goreleaser configuration not found");
+        let builds: Vec<&Build> = goreleaser.linux_builds().collect();
+        assert_eq!(builds.len(), 2);
+        assert_eq!(builds[0].binary, Some("foo".to_string()));
+        assert_eq!(builds[1].binary, Some("foo-nightly".to_string()));
+        assert_eq!(builds[0].main_directory(), Some("foo".to_string()));
+        assert_eq!(builds[0].tags.to_vec(), vec!["netgo"]);
+        assert_eq!(builds[0].env, vec!["CGO_ENABLED=0"]);
+
+        let snapcraft = goreleaser.snapcraft().expect("snapcraft not found");
+        assert_eq!(snapcraft.grade, Some("stable".to_string()));
+        assert_eq!(
+            snapcraft.apps.get("foo").map(|a| a.plugs.clone()),
+            Some(vec!["home".to_string(), "network".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_load_invalid() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join(".goreleaser.yml"),
+            "builds: foo
+",
+        )
+        .expect("unable to write .goreleaser.yml");
+
+        assert!(Goreleaser::load(tmp_dir.path())
+            .expect("unable to load goreleaser configuration")
+            .is_none());
+    }
+}
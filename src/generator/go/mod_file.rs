@@ -0,0 +1,239 @@
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+/// A module version (i.e `golang.org/x/sys v0.1.0`)
+#[derive(Debug, PartialEq, Clone)]
+pub struct ModuleVersion {
+    pub path: String,
+    /// The version, which is not set for a local replacement
+    pub version: Option<String>,
+}
+
+/// A require directive
+#[derive(Debug, PartialEq)]
+pub struct Require {
+    pub module: ModuleVersion,
+    /// Whether the module is only required by dependencies (// indirect)
+    pub indirect: bool,
+}
+
+/// A replace directive
+#[derive(Debug, PartialEq)]
+pub struct Replace {
+    pub old: ModuleVersion,
+    pub new: ModuleVersion,
+}
+
+/// The go.mod file (or the go.work one, which shares the same syntax)
+#[derive(Debug, Default)]
+pub struct ModFile {
+    pub import_path: String,
+    pub go_version: String,
+    pub toolchain: Option<String>,
+    pub requires: Vec<Require>,
+    pub replaces: Vec<Replace>,
+    pub excludes: Vec<ModuleVersion>,
+    /// The module directories used by the workspace (go.work only)
+    pub uses: Vec<String>,
+}
+
+impl ModFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ModFile> {
+        let content = fs::read_to_string(path)?;
+        Ok(ModFile::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> ModFile {
+        let mut mod_file = ModFile::default();
+        let mut block: Option<String> = None;
+
+        for line in content.lines() {
+            let (tokens, comment) = tokenize(line);
+
+            if let Some(verb) = &block {
+                if tokens.first().map(|t| t.as_str()) == Some(")") {
+                    block = None;
+                } else if !tokens.is_empty() {
+                    mod_file.apply(verb, &tokens, comment);
+                }
+                continue;
+            }
+
+            match tokens.split_first() {
+                // an empty block (i.e `require ()`) is opened & closed on the same line
+                Some((_, rest)) if rest.len() >= 2 && rest[0] == "(" && rest[1] == ")" => {}
+                Some((verb, rest)) if rest.first().map(|t| t.as_str()) == Some("(") => {
+                    block = Some(verb.clone());
+                }
+                Some((verb, rest)) => mod_file.apply(verb, rest, comment),
+                None => {}
+            }
+        }
+
+        mod_file
+    }
+
+    /// The paths of the required modules
+    pub fn dependencies(&self) -> Vec<&str> {
+        self.requires
+            .iter()
+            .map(|r| r.module.path.as_str())
+            .collect()
+    }
+
+    // apply the given directive (verb & arguments)
+    fn apply(&mut self, verb: &str, args: &[String], comment: Option<&str>) {
+        match (verb, args) {
+            ("module", [path, ..]) => self.import_path = path.clone(),
+            ("go", [version, ..]) => self.go_version = version.clone(),
+            ("toolchain", [toolchain, ..]) => self.toolchain = Some(toolchain.clone()),
+            ("require", [path, version, ..]) => self.requires.push(Require {
+                module: module_version(path, Some(version)),
+                indirect: comment.map(|c| c.trim() == "indirect").unwrap_or(false),
+            }),
+            ("use", [directory, ..]) => self.uses.push(directory.clone()),
+            ("exclude", [path, version, ..]) => {
+                self.excludes.push(module_version(path, Some(version)))
+            }
+            ("replace", args) => {
+                if let Some(pos) = args.iter().position(|a| a == "=>") {
+                    let (old, new) = (&args[..pos], &args[pos + 1..]);
+                    if let (Some(old_path), Some(new_path)) = (old.first(), new.first()) {
+                        self.replaces.push(Replace {
+                            old: module_version(old_path, old.get(1)),
+                            new: module_version(new_path, new.get(1)),
+                        });
+                    }
+                }
+            }
+            (verb, _) => log::debug!("Ignoring go.mod directive {}", verb),
+        }
+    }
+}
+
+fn module_version(path: &str, version: Option<&String>) -> ModuleVersion {
+    ModuleVersion {
+        path: path.to_string(),
+        version: version.cloned(),
+    }
+}
+
+// split a go.mod line into its tokens (unquoting them) and its trailing comment
+fn tokenize(line: &str) -> (Vec<String>, Option<&str>) {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if line[i..].starts_with("//") => return (tokens, Some(&line[i + 2..])),
+            '"' | '`' => {
+                let mut token = String::new();
+                while let Some((_, next)) = chars.next() {
+                    match next {
+                        '\\' if c == '"' => {
+                            if let Some((_, escaped)) = chars.next() {
+                                token.push(escaped);
+                            }
+                        }
+                        next if next == c => break,
+                        next => token.push(next),
+                    }
+                }
+                tokens.push(token);
+            }
+            '(' | ')' => tokens.push(c.to_string()),
+            _ => {
+                let mut token = c.to_string();
+                while let Some((_, next)) = chars.peek() {
+                    if next.is_whitespace() || *next == '(' || *next == ')' || *next == '"' {
+                        break;
+                    }
+                    token.push(*next);
+                    chars.next();
+                }
+                // comments may be stuck to the last token
+                if let Some(pos) = token.find("//") {
+                    let start = i + pos;
+                    token.truncate(pos);
+                    if !token.is_empty() {
+                        tokens.push(token);
+                    }
+                    return (tokens, Some(&line[start + 2..]));
+                }
+                tokens.push(token);
+            }
+        }
+    }
+
+    (tokens, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let content = "// The foo module
+module \"github.com/creekorful/foo\"
+
+go 1.21
+toolchain go1.21.3
+
+require golang.org/x/sys v0.13.0
+
+require (
+    github.com/gin-gonic/gin v1.9.1 // web framework
+    github.com/mattn/go-sqlite3 v1.14.17
+    golang.org/x/net v0.17.0 // indirect
+)
+
+replace github.com/foo/bar v1.0.0 => ../bar
+
+exclude (
+    golang.org/x/crypto v0.1.0
+)
+";
+        let mod_file = ModFile::parse(content);
+        assert_eq!(mod_file.import_path, "github.com/creekorful/foo");
+        assert_eq!(mod_file.go_version, "1.21");
+        assert_eq!(mod_file.toolchain, Some("go1.21.3".to_string()));
+        assert_eq!(
+            mod_file.dependencies(),
+            vec![
+                "golang.org/x/sys",
+                "github.com/gin-gonic/gin",
+                "github.com/mattn/go-sqlite3",
+                "golang.org/x/net"
+            ]
+        );
+        assert!(!mod_file.requires[1].indirect);
+        assert!(mod_file.requires[3].indirect);
+        assert_eq!(
+            mod_file.replaces,
+            vec![Replace {
+                old: ModuleVersion {
+                    path: "github.com/foo/bar".to_string(),
+                    version: Some("v1.0.0".to_string()),
+                },
+                new: ModuleVersion {
+                    path: "../bar".to_string(),
+                    version: None,
+                },
+            }]
+        );
+        assert_eq!(mod_file.excludes.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_empty_block() {
+        let content =
+            "module foo\n\nrequire ()\n\nrequire golang.org/x/sys v0.13.0\nexclude ( )\ngo 1.21\n";
+        let mod_file = ModFile::parse(content);
+        assert_eq!(mod_file.dependencies(), vec!["golang.org/x/sys"]);
+        assert_eq!(mod_file.go_version, "1.21");
+        assert!(mod_file.excludes.is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+use crate::snap::{File, Hook, Part};
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+const CONFIG_DIRECTORIES: [&str; 3] = [".", "config", "etc"];
+const CONFIG_SUFFIXES: [&str; 4] = ["", ".example", ".sample", ".dist"];
+const CONFIG_FORMATS: [(&str, Format); 3] = [
+    ("config.toml", Format::Toml),
+    ("config.yaml", Format::Yaml),
+    ("config.yml", Format::Yaml),
+];
+
+/// The format of a configuration file
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    Toml,
+    Yaml,
+}
+
+/// A default configuration file shipped by the source
+#[derive(Debug, PartialEq)]
+pub struct DefaultConfig {
+    /// The name of the configuration file once installed (i.e config.toml)
+    pub name: String,
+    /// Path of the file, relative to the source root
+    pub path: String,
+    pub format: Format,
+    /// The top-level keys of the configuration
+    pub keys: Vec<String>,
+}
+
+impl DefaultConfig {
+    /// Find the default configuration file shipped in given source, if any.
+    pub fn find<P: AsRef<Path>>(source_path: P) -> Result<Option<DefaultConfig>> {
+        for directory in CONFIG_DIRECTORIES.iter() {
+            for (name, format) in CONFIG_FORMATS.iter() {
+                for suffix in CONFIG_SUFFIXES.iter() {
+                    // support both config.example.toml and config.toml.example
+                    let (stem, extension) = name.split_once('.').unwrap_or((name, ""));
+                    let candidates = [
+                        format!("{}{}", name, suffix),
+                        format!("{}{}.{}", stem, suffix, extension),
+                    ];
+
+                    for candidate in candidates.iter() {
+                        let path = Path::new(directory).join(candidate);
+                        let full_path = source_path.as_ref().join(&path);
+                        if full_path.is_file() {
+                            log::debug!("Found default configuration ({})", full_path.display());
+                            let content = fs::read_to_string(&full_path)?;
+                            return Ok(Some(DefaultConfig {
+                                name: name.to_string(),
+                                path: path
+                                    .strip_prefix(".")
+                                    .unwrap_or(&path)
+                                    .to_string_lossy()
+                                    .to_string(),
+                                format: *format,
+                                keys: top_level_keys(&content, *format),
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Ship the default configuration using a dump part, and scaffold the hooks managing
+    /// it: the install hook will copy it into $SNAP_COMMON, the post-refresh hook will merge
+    /// the keys added by new versions, and the configure hook will map the `snap set` keys
+    /// into it.
+    pub fn apply(&self, snap: &mut File) {
+        log::debug!(
+            "Scaffolding install, post-refresh and configure hooks for {}",
+            self.name
+        );
+        let mut part = Part::new("dump");
+        part.stage = Some(vec![self.path.clone()]);
+        snap.parts.insert("config".to_string(), part);
+
+        let hooks = [
+            ("install", self.install_hook()),
+            ("post-refresh", self.post_refresh_hook()),
+            ("configure", self.configure_hook()),
+        ];
+        for (name, script) in hooks {
+            snap.hooks.insert(
+                name.to_string(),
+                Hook {
+                    plugs: None,
+                    script: Some(script),
+                },
+            );
+        }
+    }
+
+    fn install_hook(&self) -> String {
+        format!(
+            "#!/bin/sh -e
+
+# Install the default configuration
+if [ ! -f \"$SNAP_COMMON/{name}\" ]; then
+    cp \"$SNAP/{path}\" \"$SNAP_COMMON/{name}\"
+fi
+",
+            name = self.name,
+            path = self.path,
+        )
+    }
+
+    fn post_refresh_hook(&self) -> String {
+        let top_level = match self.format {
+            // the keys located after the first TOML table are not top-level ones
+            Format::Toml => "awk '/^\\[/ { exit } { print }'",
+            Format::Yaml => "cat",
+        };
+
+        let mut hook = format!(
+            "#!/bin/sh -e
+
+CONFIG=\"$SNAP_COMMON/{name}\"
+DEFAULT=\"$SNAP/{path}\"
+
+if [ ! -f \"$CONFIG\" ]; then
+    cp \"$DEFAULT\" \"$CONFIG\"
+    exit 0
+fi
+
+# Add top-level key $1 from the default configuration (if missing)
+add_key() {{
+    if ! {top_level} \"$CONFIG\" | grep -Eq \"^$1 *[=:]\"; then
+        {{ grep -E \"^$1 *[=:]\" \"$DEFAULT\" | head -n 1; cat \"$CONFIG\"; }} > \"$CONFIG.new\"
+        mv \"$CONFIG.new\" \"$CONFIG\"
+    fi
+}}
+
+",
+            name = self.name,
+            path = self.path,
+            top_level = top_level,
+        );
+
+        for key in &self.keys {
+            hook.push_str(&format!("add_key {}\n", key));
+        }
+
+        hook
+    }
+
+    fn configure_hook(&self) -> String {
+        let (separator, range) = match self.format {
+            // only replace keys located before the first TOML table
+            Format::Toml => (" = ", "1,/^\\[/ "),
+            Format::Yaml => (": ", ""),
+        };
+
+        let mut hook = format!(
+            "#!/bin/sh -e
+
+CONFIG=\"$SNAP_COMMON/{name}\"
+
+# Set configuration key $1 using value of snap option $2 (if set)
+set_value() {{
+    value=\"$(snapctl get \"$2\")\"
+    if [ -z \"$value\" ]; then
+        return 0
+    fi
+    if ! printf '%s' \"$value\" | tr '\\n' ' ' | grep -Eq '^(true|false|[0-9]+(\\.[0-9]+)?)$'; then
+        # quote the value, escaping backslashes, double quotes and newlines
+        value=\"\\\"$(printf '%s' \"$value\" | sed -e 's/[\\\\\"]/\\\\&/g' -e '$!s/$/\\\\n/' | tr -d '\\n')\\\"\"
+    fi
+    # escape the characters having a special meaning in the sed replacement
+    value=\"$(printf '%s' \"$value\" | sed -e 's/[\\\\|&]/\\\\&/g')\"
+    sed -i \"{range}s|^$1 *[=:].*|$1{separator}$value|\" \"$CONFIG\"
+}}
+
+",
+            name = self.name,
+            range = range,
+            separator = separator,
+        );
+
+        for key in &self.keys {
+            hook.push_str(&format!("set_value {} {}\n", key, snap_option_name(key)));
+        }
+
+        hook
+    }
+}
+
+// snap options only allow lowercase letters, digits and dashes
+fn snap_option_name(key: &str) -> String {
+    key.to_lowercase().replace('_', "-")
+}
+
+fn top_level_keys(content: &str, format: Format) -> Vec<String> {
+    let mut keys = Vec::new();
+
+    for line in content.lines() {
+        if format == Format::Toml && line.trim_start().starts_with('[') {
+            break;
+        }
+        if line.starts_with(char::is_whitespace) || line.starts_with('#') {
+            continue;
+        }
+
+        let separator = match format {
+            Format::Toml => '=',
+            Format::Yaml => ':',
+        };
+        if let Some((key, value)) = line.split_once(separator) {
+            let key = key.trim();
+            if !key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                && is_scalar(value.trim(), format)
+            {
+                keys.push(key.to_string());
+            }
+        }
+    }
+
+    keys
+}
+
+// only single line scalars can be replaced by the configure hook
+// (not tables, arrays, multi-line strings or nested mappings)
+fn is_scalar(value: &str, format: Format) -> bool {
+    let excluded: &[&str] = match format {
+        Format::Toml => &["[", "{", "\"\"\"", "'''"],
+        Format::Yaml => &["#", "[", "{", "|", ">", "&", "*", "!"],
+    };
+    !value.is_empty() && !excluded.iter().any(|p| value.starts_with(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_find_default_config() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("config")).expect("unable to create config");
+        fs::write(
+            tmp_dir.path().join("config").join("config.example.toml"),
+            "# Listen address\nlisten_addr = \"127.0.0.1\"\nworkers = 4\npeers = [\"a\"]\nlimits = { max = 1 }\n\n[database]\nurl = \"\"\n",
+        )
+        .expect("unable to write config/config.example.toml");
+
+        let config = DefaultConfig::find(tmp_dir.path())
+            .expect("unable to find default config")
+            .expect("default config not found");
+        assert_eq!(config.name, "config.toml");
+        assert_eq!(config.path, "config/config.example.toml");
+        assert_eq!(config.format, Format::Toml);
+        assert_eq!(config.keys, vec!["listen_addr", "workers"]);
+    }
+
+    #[test]
+    fn test_top_level_keys_yaml() {
+        let content =
+            "server:\n  port: 8080\nlog-level: info\nhosts: [a, b]\nmotd: |\n  Hello\n# comment: true\n";
+        assert_eq!(top_level_keys(content, Format::Yaml), vec!["log-level"]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let config = DefaultConfig {
+            name: "config.toml".to_string(),
+            path: "config/config.example.toml".to_string(),
+            format: Format::Toml,
+            keys: vec!["listen_addr".to_string(), "workers".to_string()],
+        };
+
+        let mut snap = File::new("foo");
+        config.apply(&mut snap);
+
+        let part = snap.parts.get("config").expect("config part not present");
+        assert_eq!(part.plugin, "dump");
+        assert_eq!(
+            part.stage,
+            Some(vec!["config/config.example.toml".to_string()])
+        );
+
+        let hook_names: Vec<&String> = snap.hooks.keys().collect();
+        assert_eq!(hook_names, vec!["configure", "install", "post-refresh"]);
+    }
+
+    #[test]
+    fn test_hooks() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let path = tmp_dir.path();
+        let snap_path = path.join("snap");
+        let common_path = path.join("common");
+        let bin_path = path.join("bin");
+        for directory in [&snap_path.join("config"), &common_path, &bin_path] {
+            fs::create_dir_all(directory).expect("unable to create directory");
+        }
+        fs::write(
+            snap_path.join("config").join("config.example.toml"),
+            "listen_addr = \"127.0.0.1\"\nworkers = 4\n\n[database]\nurl = \"\"\n",
+        )
+        .expect("unable to write default config");
+        // fake snapctl, returning the listen-addr option only
+        fs::write(
+            bin_path.join("snapctl"),
+            "#!/bin/sh\nif [ \"$2\" = listen-addr ]; then echo '0.0.0.0'; fi\n",
+        )
+        .expect("unable to write snapctl");
+        fs::set_permissions(bin_path.join("snapctl"), fs::Permissions::from_mode(0o755))
+            .expect("unable to set snapctl permissions");
+
+        let config = DefaultConfig::find(&snap_path)
+            .expect("unable to find default config")
+            .expect("default config not found");
+        let mut snap = File::new("foo");
+        config.apply(&mut snap);
+
+        let run_hook = |name: &str| {
+            let script = snap
+                .hooks
+                .get(name)
+                .and_then(|h| h.script.as_ref())
+                .expect("hook script not present");
+            let hook_path = path.join(name);
+            fs::write(&hook_path, script).expect("unable to write hook");
+
+            let status = Command::new("sh")
+                .arg("-n")
+                .arg(&hook_path)
+                .status()
+                .expect("unable to check hook syntax");
+            assert!(status.success(), "invalid {} hook syntax", name);
+
+            let path_env = format!(
+                "{}:{}",
+                bin_path.display(),
+                std::env::var("PATH").unwrap_or_default()
+            );
+            let status = Command::new("sh")
+                .arg(&hook_path)
+                .env("SNAP", &snap_path)
+                .env("SNAP_COMMON", &common_path)
+                .env("PATH", path_env)
+                .status()
+                .expect("unable to run hook");
+            assert!(status.success(), "{} hook failed", name);
+        };
+        let installed_config = || {
+            fs::read_to_string(common_path.join("config.toml"))
+                .expect("unable to read installed config")
+        };
+
+        run_hook("install");
+        assert_eq!(
+            installed_config(),
+            "listen_addr = \"127.0.0.1\"\nworkers = 4\n\n[database]\nurl = \"\"\n"
+        );
+
+        run_hook("configure");
+        assert_eq!(
+            installed_config(),
+            "listen_addr = \"0.0.0.0\"\nworkers = 4\n\n[database]\nurl = \"\"\n"
+        );
+
+        // the user changes are kept, the missing keys are restored
+        fs::write(
+            common_path.join("config.toml"),
+            "listen_addr = \"0.0.0.0\"\n\n[database]\nurl = \"\"\nworkers = 2\n",
+        )
+        .expect("unable to write installed config");
+        run_hook("post-refresh");
+        assert_eq!(
+            installed_config(),
+            "workers = 4\nlisten_addr = \"0.0.0.0\"\n\n[database]\nurl = \"\"\nworkers = 2\n"
+        );
+        run_hook("install");
+        run_hook("post-refresh");
+        assert_eq!(
+            installed_config(),
+            "workers = 4\nlisten_addr = \"0.0.0.0\"\n\n[database]\nurl = \"\"\nworkers = 2\n"
+        );
+    }
+}
@@ -0,0 +1,794 @@
+use crate::generator::go::ModFile;
+use crate::generator::python::classifier_license;
+use crate::generator::rust::Registry;
+use crate::generator::{match_license, LICENSE_CACHE};
+use crate::snap::{File, THIRD_PARTY_LICENSES};
+use crate::Result;
+use askalono::Store;
+use cargo_lock::Lockfile;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LICENSE_FILES: [&str; 7] = [
+    "LICENSE",
+    "LICENSE.md",
+    "LICENSE.txt",
+    "LICENCE",
+    "COPYING",
+    "COPYING.md",
+    "COPYING.txt",
+];
+
+/// The python virtual environments in which the requirements may be installed
+const VIRTUAL_ENVIRONMENTS: [&str; 3] = [".venv", "venv", "env"];
+
+/// A lockfile (Cargo.lock, go.sum or requirements.txt) used to build a part
+#[derive(Debug, PartialEq)]
+pub struct DependencyLock {
+    pub path: PathBuf,
+    /// The names of the packages linked into the snap, if known (otherwise all the
+    /// locked packages are)
+    pub linked_packages: Option<BTreeSet<String>>,
+}
+
+/// A third-party dependency locked by the source
+#[derive(Debug, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    /// The locked version (not set for unpinned python requirements)
+    pub version: Option<String>,
+    /// The dependency ecosystem (rust, go or python)
+    pub ecosystem: &'static str,
+    /// The license (SPDX expression), if found
+    pub license: Option<String>,
+}
+
+/// The licenses of the third-party dependencies locked by a source (in Cargo.lock,
+/// go.sum or requirements.txt), found using the vendored dependencies or the local
+/// registry caches.
+#[derive(Debug, Default, PartialEq)]
+pub struct ThirdPartyLicenses {
+    pub dependencies: Vec<Dependency>,
+}
+
+impl ThirdPartyLicenses {
+    /// Find the licenses of the dependencies locked by given lockfiles.
+    pub fn find(locks: &[DependencyLock]) -> Result<ThirdPartyLicenses> {
+        let mut detector = Detector::default();
+        let mut dependencies = Vec::new();
+
+        for lock in locks {
+            let path = lock.path.as_path();
+            let directory = path.parent().unwrap_or_else(|| Path::new("."));
+            log::debug!("Inventorying licenses of {}", path.display());
+            match path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .as_ref()
+            {
+                "Cargo.lock" => dependencies.extend(cargo_dependencies(
+                    path,
+                    directory,
+                    lock.linked_packages.as_ref(),
+                    &mut detector,
+                )?),
+                "go.sum" => dependencies.extend(go_dependencies(path, directory, &mut detector)?),
+                "requirements.txt" => {
+                    dependencies.extend(python_dependencies(path, directory, &mut detector)?)
+                }
+                _ => log::warn!("Unsupported lockfile {}", path.display()),
+            }
+        }
+
+        dependencies.sort_by(|a, b| {
+            (a.ecosystem, &a.name, &a.version).cmp(&(b.ecosystem, &b.name, &b.version))
+        });
+        dependencies.dedup_by(|a, b| {
+            a.ecosystem == b.ecosystem && a.name == b.name && a.version == b.version
+        });
+
+        Ok(ThirdPartyLicenses { dependencies })
+    }
+
+    /// The SPDX expression combining given project license with the dependencies ones
+    pub fn expression(&self, license: Option<&str>) -> Option<String> {
+        let mut licenses: Vec<String> = Vec::new();
+        let dependencies_licenses = self.dependencies.iter().filter_map(|d| d.license.as_ref());
+        for license in license
+            .into_iter()
+            .chain(dependencies_licenses.map(|l| l.as_str()))
+        {
+            let license = normalize_expression(license);
+            if !licenses.contains(&license) {
+                licenses.push(license);
+            }
+        }
+
+        // keep the project license first
+        if let Some(dependencies_licenses) = licenses.get_mut(license.is_some() as usize..) {
+            dependencies_licenses.sort();
+        }
+
+        if licenses.is_empty() {
+            None
+        } else {
+            Some(licenses.join(" AND "))
+        }
+    }
+
+    /// The THIRD_PARTY_LICENSES report of given snap, listing the dependencies per license
+    pub fn report(&self, name: &str, expression: Option<&str>) -> String {
+        let mut licenses: BTreeMap<String, Vec<&Dependency>> = BTreeMap::new();
+        for dependency in &self.dependencies {
+            let license = match &dependency.license {
+                Some(license) => normalize_expression(license),
+                None => "UNKNOWN".to_string(),
+            };
+            licenses.entry(license).or_default().push(dependency);
+        }
+
+        let mut report = format!("Third-party licenses of {}\n\n", name);
+        if let Some(expression) = expression {
+            report.push_str(&format!("Combined license: {}\n", expression));
+        }
+        for (license, dependencies) in licenses {
+            report.push_str(&format!("\n{}:\n", license));
+            for dependency in dependencies {
+                report.push_str(&format!(
+                    "  - {} {} ({})\n",
+                    dependency.name,
+                    dependency.version.as_deref().unwrap_or("*"),
+                    dependency.ecosystem
+                ));
+            }
+        }
+
+        report
+    }
+
+    /// Set the THIRD_PARTY_LICENSES report of given snap, and its license to the
+    /// combined expression if asked to.
+    pub fn apply(&self, snap: &mut File, combined_license: bool) {
+        if self.dependencies.is_empty() {
+            return;
+        }
+
+        let license = Some(snap.license.as_str()).filter(|l| *l != "TODO");
+        let expression = self.expression(license);
+
+        let unknown = self
+            .dependencies
+            .iter()
+            .filter(|d| d.license.is_none())
+            .count();
+        if unknown > 0 {
+            log::warn!(
+                "Unable to find the license of {} dependencies (see {})",
+                unknown,
+                THIRD_PARTY_LICENSES
+            );
+        }
+
+        if combined_license {
+            if let Some(expression) = &expression {
+                log::debug!("Set snap license to `{}` (combined)", expression);
+                snap.license = expression.clone();
+            }
+        }
+        snap.third_party_licenses = Some(self.report(&snap.name, expression.as_deref()));
+    }
+}
+
+/// Detect licenses from their text, loading the license store only once needed
+#[derive(Default)]
+struct Detector {
+    store: Option<Store>,
+}
+
+impl Detector {
+    fn detect(&mut self, text: &str) -> Result<Option<String>> {
+        if self.store.is_none() {
+            self.store = Some(Store::from_cache(LICENSE_CACHE)?);
+        }
+        Ok(self
+            .store
+            .as_ref()
+            .and_then(|store| match_license(store, text)))
+    }
+
+    // detect the license of the first license file found in given directory
+    fn detect_files(&mut self, directory: &Path) -> Result<Option<String>> {
+        for file in LICENSE_FILES.iter() {
+            let path = directory.join(file);
+            if path.is_file() {
+                return self.detect(&fs::read_to_string(path)?);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn cargo_dependencies(
+    path: &Path,
+    directory: &Path,
+    linked_packages: Option<&BTreeSet<String>>,
+    detector: &mut Detector,
+) -> Result<Vec<Dependency>> {
+    let lockfile = match Lockfile::load(path) {
+        Ok(lockfile) => lockfile,
+        Err(e) => {
+            log::warn!("Unable to load {}: {}", path.display(), e);
+            return Ok(Vec::new());
+        }
+    };
+    let registry = Registry::new(directory);
+
+    let mut dependencies = Vec::new();
+    // the packages without source are the ones of the workspace, and the ones not
+    // linked are only used at build time or by the tests
+    for package in lockfile.packages.iter().filter(|p| {
+        p.source.is_some()
+            && linked_packages
+                .map(|linked| linked.contains(p.name.as_str()))
+                .unwrap_or(true)
+    }) {
+        let version = package.version.to_string();
+        let license = match registry.load_manifest(package.name.as_str(), &version) {
+            Some((crate_path, manifest)) => {
+                let manifest_package = manifest.get("package");
+                match manifest_package
+                    .and_then(|p| p.get("license"))
+                    .and_then(|l| l.as_str())
+                {
+                    // the legacy `/` separator means OR
+                    Some(license) => Some(license.split('/').collect::<Vec<&str>>().join(" OR ")),
+                    None => match manifest_package
+                        .and_then(|p| p.get("license-file"))
+                        .and_then(|l| l.as_str())
+                    {
+                        Some(file) => match fs::read_to_string(crate_path.join(file)) {
+                            Ok(text) => detector.detect(&text)?,
+                            Err(e) => {
+                                log::warn!(
+                                    "Unable to read the license file of {} {}: {}",
+                                    package.name,
+                                    version,
+                                    e
+                                );
+                                None
+                            }
+                        },
+                        None => detector.detect_files(&crate_path)?,
+                    },
+                }
+            }
+            None => None,
+        };
+
+        dependencies.push(Dependency {
+            name: package.name.to_string(),
+            version: Some(version),
+            ecosystem: "rust",
+            license,
+        });
+    }
+
+    Ok(dependencies)
+}
+
+fn go_dependencies(
+    path: &Path,
+    directory: &Path,
+    detector: &mut Detector,
+) -> Result<Vec<Dependency>> {
+    let module_cache = go_module_cache();
+
+    let mut dependencies = Vec::new();
+    for (module, version) in go_modules(path, directory)? {
+        let mut module_directories = vec![directory.join("vendor").join(&module)];
+        if let Some(module_cache) = &module_cache {
+            module_directories.push(module_cache.join(format!(
+                "{}@{}",
+                escape_module_path(&module),
+                version
+            )));
+        }
+
+        let mut license = None;
+        for module_directory in module_directories.iter().filter(|d| d.is_dir()) {
+            license = detector.detect_files(module_directory)?;
+            if license.is_some() {
+                break;
+            }
+        }
+
+        dependencies.push(Dependency {
+            name: module,
+            version: Some(version),
+            ecosystem: "go",
+            license,
+        });
+    }
+
+    Ok(dependencies)
+}
+
+// the modules (and their version) built by the go module of given directory: the ones
+// required by its go.mod, or else the latest version of the ones listed in its go.sum
+// (which also has the versions considered during the module graph resolution)
+fn go_modules(go_sum: &Path, directory: &Path) -> Result<Vec<(String, String)>> {
+    let go_mod = directory.join("go.mod");
+    if go_mod.is_file() {
+        let mod_file = ModFile::load(go_mod)?;
+        return Ok(mod_file
+            .requires
+            .iter()
+            .filter_map(|require| {
+                // the replacements without version are local directories of the source
+                let module = mod_file
+                    .replaces
+                    .iter()
+                    .find(|r| {
+                        r.old.path == require.module.path
+                            && (r.old.version.is_none() || r.old.version == require.module.version)
+                    })
+                    .map(|r| &r.new)
+                    .unwrap_or(&require.module);
+                Some((module.path.clone(), module.version.clone()?))
+            })
+            .collect());
+    }
+
+    let mut modules: BTreeMap<String, String> = BTreeMap::new();
+    for line in fs::read_to_string(go_sum)?.lines() {
+        // the `<module> <version>/go.mod` lines are only the go.mod checksums
+        let (module, version) = match line.split_whitespace().collect::<Vec<&str>>()[..] {
+            [module, version, _] if !version.ends_with("/go.mod") => (module, version),
+            _ => continue,
+        };
+
+        let latest = modules
+            .entry(module.to_string())
+            .or_insert_with(|| version.to_string());
+        if compare_module_versions(version, latest) == Ordering::Greater {
+            *latest = version.to_string();
+        }
+    }
+
+    Ok(modules.into_iter().collect())
+}
+
+// compare two module versions (i.e `v1.10.0` > `v1.10.0-rc.1` > `v1.9.2`)
+fn compare_module_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .filter_map(|p| p.parse().ok())
+            .collect()
+    };
+    // pre-releases precede the release
+    parse(a)
+        .cmp(&parse(b))
+        .then_with(|| b.contains('-').cmp(&a.contains('-')))
+}
+
+fn python_dependencies(
+    path: &Path,
+    directory: &Path,
+    detector: &mut Detector,
+) -> Result<Vec<Dependency>> {
+    let distributions = find_distributions(directory)?;
+
+    let mut dependencies = Vec::new();
+    for line in fs::read_to_string(path)?.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        // skip the options (i.e `-r other.txt`)
+        if line.is_empty() || line.starts_with('-') {
+            continue;
+        }
+
+        let name_len = line
+            .find(|c: char| !c.is_alphanumeric() && !['-', '_', '.'].contains(&c))
+            .unwrap_or(line.len());
+        let name = normalize_name(&line[..name_len]);
+        let version = line.split_once("==").map(|(_, version)| {
+            version
+                .split(|c: char| c == ';' || c == ',' || c.is_whitespace())
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        });
+
+        let distribution = distributions.iter().find(|(n, v, _)| {
+            *n == name && version.as_ref().map(|version| version == v).unwrap_or(true)
+        });
+        let license = match distribution {
+            Some((_, _, path)) => distribution_license(path, detector)?,
+            None => None,
+        };
+
+        dependencies.push(Dependency {
+            name,
+            version,
+            ecosystem: "python",
+            license,
+        });
+    }
+
+    Ok(dependencies)
+}
+
+// find the distributions (name, version, dist-info path) installed in the virtual
+// environments of given directory
+fn find_distributions(directory: &Path) -> Result<Vec<(String, String, PathBuf)>> {
+    let mut distributions = Vec::new();
+
+    for environment in VIRTUAL_ENVIRONMENTS.iter() {
+        let lib_path = directory.join(environment).join("lib");
+        if !lib_path.is_dir() {
+            continue;
+        }
+
+        for python in fs::read_dir(lib_path)? {
+            let site_packages = python?.path().join("site-packages");
+            if !site_packages.is_dir() {
+                continue;
+            }
+
+            for entry in fs::read_dir(site_packages)? {
+                let path = entry?.path();
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                if let Some((name, version)) = file_name
+                    .strip_suffix(".dist-info")
+                    .and_then(|d| d.split_once('-'))
+                {
+                    distributions.push((normalize_name(name), version.to_string(), path.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(distributions)
+}
+
+// find the license of given distribution using its metadata or its license files
+fn distribution_license(path: &Path, detector: &mut Detector) -> Result<Option<String>> {
+    let metadata = fs::read_to_string(path.join("METADATA")).unwrap_or_default();
+    let headers = metadata.split("\n\n").next().unwrap_or_default();
+
+    for line in headers.lines() {
+        if let Some(expression) = line.strip_prefix("License-Expression:") {
+            return Ok(Some(expression.trim().to_string()));
+        }
+    }
+    for line in headers.lines() {
+        if let Some(license) = line
+            .strip_prefix("Classifier:")
+            .and_then(classifier_license)
+        {
+            return Ok(Some(license));
+        }
+    }
+
+    match detector.detect_files(path)? {
+        Some(license) => Ok(Some(license)),
+        None => detector.detect_files(&path.join("licenses")),
+    }
+}
+
+// normalize given license expression to be combined using AND: alternatives
+// (i.e `MIT OR Apache-2.0`) are sorted and enclosed, since AND takes precedence over OR
+fn normalize_expression(license: &str) -> String {
+    let license = license.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let mut alternatives = alternatives(&license);
+    if alternatives.len() == 1 {
+        return alternatives.remove(0);
+    }
+
+    alternatives.sort_unstable();
+    alternatives.dedup();
+    format!("({})", alternatives.join(" OR "))
+}
+
+// the top-level alternatives of given expression, flattening the enclosed ones
+// (i.e `(MIT OR ISC) OR Apache-2.0` -> MIT, ISC, Apache-2.0)
+fn alternatives(expression: &str) -> Vec<String> {
+    let expression = strip_parentheses(expression);
+
+    let mut alternatives = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in expression.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ' ' if depth == 0 && expression[i..].starts_with(" OR ") => {
+                alternatives.push(&expression[start..i]);
+                start = i + " OR ".len();
+            }
+            _ => {}
+        }
+    }
+
+    if alternatives.is_empty() {
+        return vec![expression.to_string()];
+    }
+    alternatives.push(&expression[start..]);
+    alternatives
+        .into_iter()
+        .flat_map(self::alternatives)
+        .collect()
+}
+
+// remove the parentheses enclosing the whole expression (i.e `(MIT OR ISC)`)
+fn strip_parentheses(expression: &str) -> &str {
+    let mut expression = expression.trim();
+    while let Some(inner) = expression
+        .strip_prefix('(')
+        .and_then(|e| e.strip_suffix(')'))
+    {
+        // make sure the first parenthesis is closed by the last one (i.e not `(A) AND (B)`)
+        let mut depth = 0;
+        let encloses = inner.chars().all(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth >= 0
+        });
+        if !encloses {
+            break;
+        }
+        expression = inner.trim();
+    }
+    expression
+}
+
+// normalize given python project name (PEP 503)
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+fn go_module_cache() -> Option<PathBuf> {
+    if let Some(module_cache) = env::var_os("GOMODCACHE") {
+        return Some(PathBuf::from(module_cache));
+    }
+
+    let go_path = env::var_os("GOPATH")
+        .and_then(|paths| env::split_paths(&paths).next())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join("go")))?;
+    Some(go_path.join("pkg").join("mod"))
+}
+
+// escape the uppercase letters of given module path, as done by the module cache
+// (i.e github.com/BurntSushi/toml -> github.com/!burnt!sushi/toml)
+fn escape_module_path(path: &str) -> String {
+    let mut escaped = String::new();
+    for c in path.chars() {
+        if c.is_uppercase() {
+            escaped.push('!');
+            escaped.extend(c.to_lowercase());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_find() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+
+        let crate_path = tmp_dir.path().join("vendor").join("autosnap-foo");
+        fs::create_dir_all(&crate_path).expect("unable to create vendor/autosnap-foo");
+        fs::write(
+            crate_path.join("Cargo.toml"),
+            "[package]\nname = \"autosnap-foo\"\nversion = \"0.1.0\"\nlicense = \"MIT/Apache-2.0\"\n",
+        )
+        .expect("unable to write Cargo.toml");
+        let crate_path = tmp_dir.path().join("vendor").join("autosnap-bar");
+        fs::create_dir_all(&crate_path).expect("unable to create vendor/autosnap-bar");
+        fs::write(
+            crate_path.join("Cargo.toml"),
+            "[package]\nname = \"autosnap-bar\"\nversion = \"0.2.0\"\nlicense-file = \"LICENSE-BAR\"\n",
+        )
+        .expect("unable to write Cargo.toml");
+        let crate_path = tmp_dir.path().join("vendor").join("autosnap-baz");
+        fs::create_dir_all(&crate_path).expect("unable to create vendor/autosnap-baz");
+        fs::write(
+            crate_path.join("Cargo.toml"),
+            "[package]\nname = \"autosnap-baz\"\nversion = \"0.3.0\"\nlicense = \"(Apache-2.0) OR MIT\"\n",
+        )
+        .expect("unable to write Cargo.toml");
+        fs::write(
+            tmp_dir.path().join("Cargo.lock"),
+            "[[package]]\nname = \"autosnap\"\nversion = \"0.5.0\"\n\n[[package]]\nname = \"autosnap-bar\"\nversion = \"0.2.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \"autosnap-baz\"\nversion = \"0.3.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \"autosnap-foo\"\nversion = \"0.1.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \"autosnap-test\"\nversion = \"0.4.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n",
+        )
+        .expect("unable to write Cargo.lock");
+
+        fs::write(
+            tmp_dir.path().join("go.sum"),
+            "github.com/creekorful/Bar v0.9.0 h1:abc=\ngithub.com/creekorful/Bar v1.0.0 h1:abc=\ngithub.com/creekorful/Bar v1.0.0/go.mod h1:def=\ngithub.com/creekorful/Bar v1.0.0-rc.1 h1:abc=\n",
+        )
+        .expect("unable to write go.sum");
+
+        let dist_info = tmp_dir
+            .path()
+            .join(".venv")
+            .join("lib")
+            .join("python3.10")
+            .join("site-packages")
+            .join("Baz_Lib-2.0.dist-info");
+        fs::create_dir_all(&dist_info).expect("unable to create dist-info");
+        fs::write(
+            dist_info.join("METADATA"),
+            "Metadata-Version: 2.1\nName: Baz_Lib\nClassifier: License :: OSI Approved :: MIT License\n\nThe baz library\n",
+        )
+        .expect("unable to write METADATA");
+        fs::write(
+            tmp_dir.path().join("requirements.txt"),
+            "# pinned requirements\nbaz-lib==2.0 ; python_version >= \"3.8\"\n-r dev.txt\nqux\n",
+        )
+        .expect("unable to write requirements.txt");
+
+        // autosnap-test is a dev-dependency
+        let linked_packages = ["autosnap-bar", "autosnap-baz", "autosnap-foo"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let locks = vec![
+            DependencyLock {
+                path: tmp_dir.path().join("Cargo.lock"),
+                linked_packages: Some(linked_packages),
+            },
+            DependencyLock {
+                path: tmp_dir.path().join("go.sum"),
+                linked_packages: None,
+            },
+            DependencyLock {
+                path: tmp_dir.path().join("requirements.txt"),
+                linked_packages: None,
+            },
+        ];
+        let licenses = ThirdPartyLicenses::find(&locks).expect("unable to find licenses");
+        assert_eq!(
+            licenses.dependencies,
+            vec![
+                Dependency {
+                    name: "github.com/creekorful/Bar".to_string(),
+                    version: Some("v1.0.0".to_string()),
+                    ecosystem: "go",
+                    license: None,
+                },
+                Dependency {
+                    name: "baz-lib".to_string(),
+                    version: Some("2.0".to_string()),
+                    ecosystem: "python",
+                    license: Some("MIT".to_string()),
+                },
+                Dependency {
+                    name: "qux".to_string(),
+                    version: None,
+                    ecosystem: "python",
+                    license: None,
+                },
+                Dependency {
+                    name: "autosnap-bar".to_string(),
+                    version: Some("0.2.0".to_string()),
+                    ecosystem: "rust",
+                    license: None,
+                },
+                Dependency {
+                    name: "autosnap-baz".to_string(),
+                    version: Some("0.3.0".to_string()),
+                    ecosystem: "rust",
+                    license: Some("(Apache-2.0) OR MIT".to_string()),
+                },
+                Dependency {
+                    name: "autosnap-foo".to_string(),
+                    version: Some("0.1.0".to_string()),
+                    ecosystem: "rust",
+                    license: Some("MIT OR Apache-2.0".to_string()),
+                },
+            ]
+        );
+
+        assert_eq!(
+            licenses.expression(Some("GPL-3.0")),
+            Some("GPL-3.0 AND (Apache-2.0 OR MIT) AND MIT".to_string())
+        );
+
+        let mut snap = File::new("autosnap");
+        snap.license = "GPL-3.0".to_string();
+        licenses.apply(&mut snap, true);
+        assert_eq!(snap.license, "GPL-3.0 AND (Apache-2.0 OR MIT) AND MIT");
+        let report = snap.third_party_licenses.expect("report not set");
+        assert!(report.contains(
+            "(Apache-2.0 OR MIT):\n  - autosnap-baz 0.3.0 (rust)\n  - autosnap-foo 0.1.0 (rust)\n"
+        ));
+        assert!(report.contains("UNKNOWN:\n  - github.com/creekorful/Bar v1.0.0 (go)\n"));
+    }
+
+    #[test]
+    fn test_go_modules() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let go_sum = tmp_dir.path().join("go.sum");
+        fs::write(
+            &go_sum,
+            "golang.org/x/sys v0.1.0 h1:abc=\ngolang.org/x/sys v0.10.0 h1:abc=\ngithub.com/foo/bar v1.2.0 h1:abc=\n",
+        )
+        .expect("unable to write go.sum");
+        assert_eq!(
+            go_modules(&go_sum, tmp_dir.path()).expect("unable to find go modules"),
+            vec![
+                ("github.com/foo/bar".to_string(), "v1.2.0".to_string()),
+                ("golang.org/x/sys".to_string(), "v0.10.0".to_string()),
+            ]
+        );
+
+        fs::write(
+            tmp_dir.path().join("go.mod"),
+            "module github.com/foo/foo\n\nrequire (\n\tgolang.org/x/sys v0.1.0\n\tgithub.com/foo/bar v1.0.0\n\tgithub.com/foo/baz v1.0.0\n)\n\nreplace github.com/foo/bar => github.com/fork/bar v1.1.0\nreplace github.com/foo/baz => ./baz\n",
+        )
+        .expect("unable to write go.mod");
+        assert_eq!(
+            go_modules(&go_sum, tmp_dir.path()).expect("unable to find go modules"),
+            vec![
+                ("golang.org/x/sys".to_string(), "v0.1.0".to_string()),
+                ("github.com/fork/bar".to_string(), "v1.1.0".to_string()),
+            ]
+        );
+        assert_eq!(
+            compare_module_versions("v1.10.0", "v1.10.0-rc.1"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_normalize_expression() {
+        assert_eq!(normalize_expression("MIT"), "MIT");
+        assert_eq!(
+            normalize_expression("MIT  OR Apache-2.0"),
+            "(Apache-2.0 OR MIT)"
+        );
+        assert_eq!(
+            normalize_expression("Apache-2.0 WITH LLVM-exception OR MIT"),
+            "(Apache-2.0 WITH LLVM-exception OR MIT)"
+        );
+        assert_eq!(normalize_expression("MIT AND ISC"), "MIT AND ISC");
+        assert_eq!(
+            normalize_expression("(MIT) OR Apache-2.0"),
+            "(Apache-2.0 OR MIT)"
+        );
+        assert_eq!(
+            normalize_expression("(MIT OR ISC) OR Apache-2.0"),
+            "(Apache-2.0 OR ISC OR MIT)"
+        );
+        assert_eq!(
+            normalize_expression("(MIT OR ISC) AND (Apache-2.0)"),
+            "(MIT OR ISC) AND (Apache-2.0)"
+        );
+    }
+
+    #[test]
+    fn test_escape_module_path() {
+        assert_eq!(
+            escape_module_path("github.com/BurntSushi/toml"),
+            "github.com/!burnt!sushi/toml"
+        );
+    }
+}
@@ -1,59 +1,253 @@
-use crate::generator::{Generator, Provider};
+use crate::generator::licenses::DependencyLock;
+use crate::generator::python::pyproject::{BuildBackend, PyProject};
+use crate::generator::python::setup_cfg::SetupCfg;
+use crate::generator::python::setup_py::SetupPy;
+use crate::generator::{walk_source, Generator, Options, Provider};
 use crate::snap::{App, Part};
 use crate::Result;
 use std::collections::BTreeMap;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod license;
+mod pyproject;
+mod setup_cfg;
+mod setup_py;
+mod specifier;
+
+pub use license::{classifier_license, is_spdx_expression};
+
+/// The build requirements that need git to determinate the package version
+const VCS_BUILD_REQUIRES: [&str; 4] = [
+    "setuptools_scm",
+    "setuptools-scm",
+    "hatch-vcs",
+    "poetry-dynamic-versioning",
+];
+
+/// The python version shipped by each base, from the oldest to the newest
+const BASE_PYTHON_VERSIONS: [(&str, &str); 4] = [
+    ("core18", "3.6"),
+    ("core20", "3.8"),
+    ("core22", "3.10"),
+    ("core24", "3.12"),
+];
+
+/// The python packages depending on native libraries:
+/// (package, build packages, stage packages)
+const NATIVE_DEPENDENCIES: [(&str, &[&str], &[&str]); 12] = [
+    ("cffi", &["libffi-dev"], &[]),
+    ("cryptography", &["libssl-dev", "libffi-dev"], &[]),
+    ("dbus-python", &["libdbus-1-dev", "libglib2.0-dev"], &[]),
+    (
+        "lxml",
+        &["libxml2-dev", "libxslt1-dev"],
+        &["libxml2", "libxslt1.1"],
+    ),
+    ("mysqlclient", &["libmysqlclient-dev"], &[]),
+    (
+        "pillow",
+        &["libjpeg-dev", "zlib1g-dev"],
+        &["libjpeg-turbo8"],
+    ),
+    ("psycopg2", &["libpq-dev"], &["libpq5"]),
+    ("pyaudio", &["portaudio19-dev"], &["libportaudio2"]),
+    ("pycairo", &["libcairo2-dev"], &["libcairo2"]),
+    (
+        "pygobject",
+        &["libgirepository1.0-dev", "libcairo2-dev"],
+        &[],
+    ),
+    ("python-ldap", &["libldap2-dev", "libsasl2-dev"], &[]),
+    ("pyyaml", &["libyaml-dev"], &["libyaml-0-2"]),
+];
+
+/// The extensions of C extension source files
+const C_EXTENSIONS: [&str; 4] = [".c", ".cc", ".cpp", ".pyx"];
 
 pub struct PythonGenerator {
-    setup_py: SetupPy,
+    metadata: Metadata,
+    build_backend: BuildBackend,
+    build_requires: Vec<String>,
+    has_c_extensions: bool,
+    has_requirements_txt: bool,
+    has_constraints_txt: bool,
+    base: String,
+    source_path: PathBuf,
     source_name: String,
 }
 
 pub struct PythonProvider {}
 
+/// The python package metadata
 #[derive(Default)]
-pub struct SetupPy {
+pub struct Metadata {
     name: Option<String>,
     version: Option<String>,
     description: Option<String>,
+    license: Option<String>,
+    /// The trove classifiers of the package (i.e `License :: OSI Approved :: MIT License`)
+    classifiers: Vec<String>,
+    /// The scripts installed by the package (console_scripts entry points, scripts)
+    scripts: Vec<String>,
+    /// The requirements (install_requires, dependencies) of the package
+    requirements: Vec<String>,
+    /// The python versions supported by the package (i.e `>=3.6`)
+    requires_python: Option<String>,
+}
+
+impl Metadata {
+    /// Complete missing fields using given metadata
+    fn merge(&mut self, other: Metadata) {
+        self.name = self.name.take().or(other.name);
+        self.version = self.version.take().or(other.version);
+        self.description = self.description.take().or(other.description);
+        self.license = self.license.take().or(other.license);
+        self.requires_python = self.requires_python.take().or(other.requires_python);
+        for classifier in other.classifiers {
+            if !self.classifiers.contains(&classifier) {
+                self.classifiers.push(classifier);
+            }
+        }
+        for script in other.scripts {
+            if !self.scripts.contains(&script) {
+                self.scripts.push(script);
+            }
+        }
+        for requirement in other.requirements {
+            if !self.requirements.contains(&requirement) {
+                self.requirements.push(requirement);
+            }
+        }
+    }
+
+    fn from_setup_py(setup_py: &SetupPy) -> Metadata {
+        Metadata {
+            name: setup_py.get_str("name"),
+            version: setup_py.get_str("version"),
+            description: setup_py.get_str("description"),
+            license: setup_py.get_str("license"),
+            classifiers: setup_py
+                .get("classifiers")
+                .map(|v| v.as_strings())
+                .unwrap_or_default(),
+            scripts: setup_py.scripts(),
+            requirements: setup_py
+                .get("install_requires")
+                .map(|v| v.as_strings())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|r| requirement_name(r))
+                .collect(),
+            requires_python: setup_py.get_str("python_requires"),
+        }
+    }
+
+    /// Complete missing fields by executing setup.py
+    fn complete_from_execution<P: AsRef<Path>>(&mut self, source_path: P) {
+        let fields = [
+            ("name", &mut self.name),
+            ("version", &mut self.version),
+            ("description", &mut self.description),
+            ("license", &mut self.license),
+        ];
+        for (field, value) in fields {
+            if value.is_none() {
+                *value = execute_cmd(&source_path, field);
+            }
+        }
+    }
 }
 
 impl Provider<PythonGenerator> for PythonProvider {
-    fn provide<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<PythonGenerator> {
-        // Make sure python is on path
-        Command::new("python")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()?;
-
-        // Set fields
+    fn provide<P: AsRef<Path>>(source_path: P, options: &Options) -> Result<PythonGenerator> {
+        let mut metadata = Metadata::default();
+        let mut build_backend = BuildBackend::Setuptools;
+        let mut build_requires = Vec::new();
+
+        // Metadata are looked up in pyproject.toml, then setup.cfg, then setup.py
+        if let Some(pyproject) = PyProject::load(&source_path)? {
+            metadata.merge(pyproject.metadata());
+            build_backend = pyproject.build_backend();
+            build_requires = pyproject.build_requires();
+            log::debug!("Using {:?} build backend", build_backend);
+        }
+        if let Some(setup_cfg) = SetupCfg::load(&source_path)? {
+            metadata.merge(setup_cfg.metadata());
+        }
+
+        let has_setup_py = source_path.as_ref().join("setup.py").exists();
+        let mut has_c_extensions = false;
+        if has_setup_py {
+            let setup_py = SetupPy::load(&source_path)?;
+            metadata.merge(Metadata::from_setup_py(&setup_py));
+            has_c_extensions = setup_py.get("ext_modules").is_some();
+
+            // Executing setup.py runs arbitrary code from the source, only do it if allowed to
+            if options.python_exec {
+                log::debug!("Executing setup.py to complete package metadata");
+                metadata.complete_from_execution(&source_path);
+            }
+        }
+
+        // Requirements can also be pinned using a requirements.txt
+        let requirements_txt = source_path.as_ref().join("requirements.txt");
+        let has_requirements_txt = requirements_txt.exists();
+        if has_requirements_txt {
+            metadata.merge(Metadata {
+                requirements: fs::read_to_string(requirements_txt)?
+                    .lines()
+                    .filter_map(requirement_name)
+                    .collect(),
+                ..Default::default()
+            });
+        }
+
+        if !has_c_extensions {
+            has_c_extensions = find_c_extensions(&source_path)?;
+        }
+
+        // The core18 & core20 python plugins can only install projects having a setup.py,
+        // PEP 517 projects need the core22 one, which runs `pip install .`
+        let min_base = if has_setup_py { "core18" } else { "core22" };
+        let base = select_base(metadata.requires_python.as_deref(), min_base)?;
+
         Ok(PythonGenerator {
-            setup_py: SetupPy {
-                name: execute_cmd(&source_path, "name"),
-                version: execute_cmd(&source_path, "version"),
-                description: execute_cmd(&source_path, "description"),
-            },
-            source_name: source_name.to_string(),
+            metadata,
+            build_backend,
+            build_requires,
+            has_c_extensions,
+            has_requirements_txt,
+            has_constraints_txt: source_path.as_ref().join("constraints.txt").exists(),
+            base,
+            source_path: source_path.as_ref().to_path_buf(),
+            source_name: options.source_name.clone(),
         })
     }
 
     fn can_provide<P: AsRef<Path>>(source_path: P) -> bool {
         source_path.as_ref().join("setup.py").exists()
+            || source_path.as_ref().join("pyproject.toml").exists()
+            || source_path.as_ref().join("setup.cfg").exists()
     }
 }
 
 impl Generator for PythonGenerator {
+    fn base(&self) -> Result<Option<String>> {
+        Ok(Some(self.base.clone()))
+    }
+
     fn name(&self) -> Result<Option<String>> {
-        Ok(self.setup_py.name.clone())
+        Ok(self.metadata.name.clone())
     }
 
     fn version(&self) -> Result<Option<String>> {
-        Ok(self.setup_py.version.clone())
+        Ok(self.metadata.version.clone())
     }
 
     fn summary(&self) -> Result<Option<String>> {
-        Ok(self.setup_py.description.clone())
+        Ok(self.metadata.description.clone())
     }
 
     fn description(&self) -> Result<Option<String>> {
@@ -61,53 +255,453 @@ impl Generator for PythonGenerator {
     }
 
     fn license(&self) -> Result<Option<String>> {
+        // the license field is free-form (i.e `BSD`, `see LICENSE`): only trust SPDX
+        // expressions and license classifiers, and let the license file detection do the rest
+        if let Some(license) = &self.metadata.license {
+            if is_spdx_expression(license)? {
+                return Ok(Some(license.clone()));
+            }
+            log::debug!("Ignoring license `{}` (not a SPDX expression)", license);
+        }
+
+        Ok(self
+            .metadata
+            .classifiers
+            .iter()
+            .find_map(|c| classifier_license(c)))
+    }
+
+    fn icon(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn website(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn source_code(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn issues(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn contact(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn grade(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn confinement(&self) -> Result<Option<String>> {
         Ok(None)
     }
 
     fn parts(&self) -> Result<BTreeMap<String, Part>> {
         let mut parts = BTreeMap::default();
 
-        parts.insert(
-            self.source_name.clone(),
-            Part {
-                plugin: "python".to_string(),
-                source: ".".to_string(),
-                build_packages: None,
-                stage_packages: None,
-                go_import_path: None,
-                python_version: Some("python3".to_string()), // TODO
-            },
-        );
+        let (mut build_packages, stage_packages) =
+            find_native_packages(&self.metadata.requirements, self.has_c_extensions);
+
+        // The package version is computed from git by these build requirements
+        if self
+            .build_requires
+            .iter()
+            .any(|r| VCS_BUILD_REQUIRES.iter().any(|v| r.starts_with(v)))
+        {
+            log::debug!(
+                "Adding git build package as required by {:?} build backend",
+                self.build_backend
+            );
+            build_packages.push("git".to_string());
+        }
+
+        let mut part = Part {
+            build_packages: Some(build_packages).filter(|p| !p.is_empty()),
+            stage_packages: Some(stage_packages).filter(|p| !p.is_empty()),
+            ..Part::new("python")
+        };
+
+        let requirements =
+            Some(vec!["requirements.txt".to_string()]).filter(|_| self.has_requirements_txt);
+        let constraints =
+            Some(vec!["constraints.txt".to_string()]).filter(|_| self.has_constraints_txt);
+
+        // the core18 plugin uses its own keys, python 2 being its default
+        if self.base == "core18" {
+            part.python_version = Some("python3".to_string());
+            part.requirements = requirements;
+            part.constraints = constraints;
+        } else {
+            part.python_requirements = requirements;
+            part.python_constraints = constraints;
+        }
+
+        parts.insert(self.source_name.clone(), part);
 
         Ok(parts)
     }
 
     fn apps(&self) -> Result<BTreeMap<String, App>> {
         let mut apps = BTreeMap::default();
-        apps.insert(
-            self.source_name.clone(),
-            App {
-                command: "TODO".to_string(),
-                plugs: None,
-            },
-        );
+
+        for script in &self.metadata.scripts {
+            log::debug!("Found executable (name: {})", script);
+            apps.insert(app_name(script), App::new(&format!("bin/{}", script)));
+        }
+
+        if apps.is_empty() {
+            apps.insert(self.source_name.clone(), App::new("TODO"));
+        }
+
         Ok(apps)
     }
+
+    fn lockfiles(&self) -> Result<Vec<DependencyLock>> {
+        if !self.has_requirements_txt {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![DependencyLock {
+            path: self.source_path.join("requirements.txt"),
+            linked_packages: None,
+        }])
+    }
+}
+
+/// Select the oldest base (starting from given one) whose python satisfies
+/// the python versions supported by the package.
+fn select_base(requires_python: Option<&str>, min_base: &str) -> Result<String> {
+    let requires_python = match requires_python {
+        Some(requires_python) => requires_python,
+        None => return Ok(min_base.to_string()),
+    };
+
+    let bases = BASE_PYTHON_VERSIONS
+        .iter()
+        .skip_while(|(base, _)| *base != min_base);
+    for (base, version) in bases {
+        if specifier::satisfies(version, requires_python) {
+            log::debug!(
+                "Using {} base (python {} satisfies {})",
+                base,
+                version,
+                requires_python
+            );
+            return Ok(base.to_string());
+        }
+    }
+
+    let python3 = BASE_PYTHON_VERSIONS
+        .iter()
+        .any(|(_, version)| specifier::satisfies(version, requires_python));
+    if !python3 && specifier::satisfies("2.7", requires_python) {
+        return Err(format!(
+            "Python 2 only projects are not supported (python_requires: {})",
+            requires_python
+        )
+        .into());
+    }
+
+    let (base, _) = BASE_PYTHON_VERSIONS[BASE_PYTHON_VERSIONS.len() - 1];
+    log::warn!(
+        "No base python satisfies {}, please check the {} base",
+        requires_python,
+        base
+    );
+    Ok(base.to_string())
+}
+
+/// Determine the build & stage packages needed by the native dependencies
+/// of the package, and by its C extensions.
+fn find_native_packages(
+    requirements: &[String],
+    has_c_extensions: bool,
+) -> (Vec<String>, Vec<String>) {
+    let mut build_packages: Vec<String> = Vec::new();
+    let mut stage_packages: Vec<String> = Vec::new();
+
+    for (dependency, build, stage) in NATIVE_DEPENDENCIES.iter() {
+        if requirements.iter().any(|r| r == dependency) {
+            log::debug!(
+                "Adding {:?} build packages and {:?} stage packages as required by {}",
+                build,
+                stage,
+                dependency
+            );
+            for package in build.iter() {
+                if !build_packages.iter().any(|p| p == package) {
+                    build_packages.push(package.to_string());
+                }
+            }
+            for package in stage.iter() {
+                if !stage_packages.iter().any(|p| p == package) {
+                    stage_packages.push(package.to_string());
+                }
+            }
+        }
+    }
+
+    // compiling C extensions (ours or the native dependencies ones) needs the python headers
+    if has_c_extensions || !build_packages.is_empty() {
+        build_packages.insert(0, "python3-dev".to_string());
+    }
+
+    (build_packages, stage_packages)
+}
+
+fn find_c_extensions<P: AsRef<Path>>(source_path: P) -> Result<bool> {
+    for entry in walk_source(source_path) {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy();
+        if entry.file_type().is_file() && C_EXTENSIONS.iter().any(|e| file_name.ends_with(e)) {
+            log::debug!("Found C extension source ({})", entry.path().display());
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Extract the normalized package name from a requirement specifier
+/// (i.e `Pillow[jpeg]>=8.0 ; python_version > "3"` -> pillow)
+fn requirement_name(requirement: &str) -> Option<String> {
+    let requirement = requirement.trim();
+    if requirement.is_empty() || requirement.starts_with('#') || requirement.starts_with('-') {
+        return None;
+    }
+
+    let name: String = requirement
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_' || *c == '.')
+        .collect();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_lowercase().replace(['_', '.'], "-"))
+    }
+}
+
+// snap app names can only contains letters, digits and dashes
+fn app_name(script: &str) -> String {
+    script
+        .trim_end_matches(".py")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Parse the name of an entry point declaration (i.e `foo = foo.cli:main`)
+fn entry_point_name(entry_point: &str) -> Option<String> {
+    entry_point
+        .split_once('=')
+        .map(|(name, _)| name.trim().to_string())
+        .filter(|name| !name.is_empty())
 }
 
 fn execute_cmd<P: AsRef<Path>>(source_path: P, field: &str) -> Option<String> {
-    let cmd = Command::new("python")
+    let output = Command::new("python3")
         .current_dir(&source_path)
         .arg("setup.py")
         .arg(format!("--{}", field))
         .output();
 
-    if cmd.is_err() {
-        return None;
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8(output.stdout)
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty() && s != "UNKNOWN"),
+        Ok(output) => {
+            log::warn!(
+                "Error while executing setup.py --{}: {}",
+                field,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            None
+        }
+        Err(e) => {
+            log::warn!("Unable to execute setup.py --{}: {}", field, e);
+            None
+        }
     }
+}
 
-    let cmd = cmd.unwrap();
-    String::from_utf8(cmd.stdout)
-        .ok()
-        .map(|s| s.replace('\n', ""))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_requirement_name() {
+        assert_eq!(
+            requirement_name("Pillow[jpeg]>=8.0 ; python_version > \"3\""),
+            Some("pillow".to_string())
+        );
+        assert_eq!(
+            requirement_name("zope.interface==5"),
+            Some("zope-interface".to_string())
+        );
+        assert_eq!(requirement_name("-r requirements-dev.txt"), None);
+        assert_eq!(requirement_name("# comment"), None);
+    }
+
+    #[test]
+    fn test_select_base() {
+        assert_eq!(select_base(None, "core18").unwrap(), "core18");
+        assert_eq!(select_base(Some(">=3.7"), "core18").unwrap(), "core20");
+        assert_eq!(select_base(Some(">=3.6"), "core22").unwrap(), "core22");
+        assert_eq!(select_base(Some(">=3.11"), "core18").unwrap(), "core24");
+        assert!(select_base(Some(">=2.7, <3"), "core18").is_err());
+    }
+
+    #[test]
+    fn test_find_native_packages() {
+        let requirements = vec![
+            "psycopg2".to_string(),
+            "lxml".to_string(),
+            "click".to_string(),
+        ];
+        let (build_packages, stage_packages) = find_native_packages(&requirements, false);
+        assert_eq!(
+            build_packages,
+            vec!["python3-dev", "libxml2-dev", "libxslt1-dev", "libpq-dev"]
+        );
+        assert_eq!(stage_packages, vec!["libxml2", "libxslt1.1", "libpq5"]);
+
+        let (build_packages, stage_packages) = find_native_packages(&[], true);
+        assert_eq!(build_packages, vec!["python3-dev"]);
+        assert!(stage_packages.is_empty());
+    }
+
+    #[test]
+    fn test_license() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join("setup.py"),
+            "from setuptools import setup\nsetup(name='foo', license='GPL-3.0-or-later')\n",
+        )
+        .expect("unable to write setup.py");
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        assert_eq!(
+            generator.license().unwrap(),
+            Some("GPL-3.0-or-later".to_string())
+        );
+
+        fs::write(
+            tmp_dir.path().join("setup.cfg"),
+            "[metadata]\nlicense = BSD\nclassifiers =\n    Programming Language :: Python :: 3\n    License :: OSI Approved :: MIT License\n",
+        )
+        .expect("unable to write setup.cfg");
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        assert_eq!(generator.license().unwrap(), Some("MIT".to_string()));
+
+        fs::write(
+            tmp_dir.path().join("setup.cfg"),
+            "[metadata]\nlicense = see LICENSE\n",
+        )
+        .expect("unable to write setup.cfg");
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        assert_eq!(generator.license().unwrap(), None);
+    }
+
+    #[test]
+    fn test_license_from_pyproject() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"foo\"\nlicense = {text = \"Apache License, Version 2.0\"}\nclassifiers = [\"License :: OSI Approved :: Apache Software License\"]\n",
+        )
+        .expect("unable to write pyproject.toml");
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        assert_eq!(generator.license().unwrap(), Some("Apache-2.0".to_string()));
+
+        fs::write(
+            tmp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"foo\"\nlicense = \"Proprietary, see LICENSE\"\n",
+        )
+        .expect("unable to write pyproject.toml");
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        assert_eq!(generator.license().unwrap(), None);
+    }
+
+    #[test]
+    fn test_apps_from_entry_points() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"foo\"\n\n[project.scripts]\nfoo = \"foo.cli:main\"\n",
+        )
+        .expect("unable to write pyproject.toml");
+        fs::write(
+            tmp_dir.path().join("setup.cfg"),
+            "[options.entry_points]\nconsole_scripts =\n    foo-admin = foo.admin:main\n",
+        )
+        .expect("unable to write setup.cfg");
+        fs::write(
+            tmp_dir.path().join("setup.py"),
+            "from setuptools import setup\nsetup(scripts=['bin/foo_legacy.py'], entry_points={'gui_scripts': ['foo-gui=foo.gui:main']})\n",
+        )
+        .expect("unable to write setup.py");
+
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        let apps = generator.apps().expect("unable to find apps");
+
+        let commands: Vec<(&str, &str)> = apps
+            .iter()
+            .map(|(name, app)| (name.as_str(), app.command.as_str()))
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                ("foo", "bin/foo"),
+                ("foo-admin", "bin/foo-admin"),
+                ("foo-gui", "bin/foo-gui"),
+                ("foo-legacy", "bin/foo_legacy.py"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lockfiles() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("docs")).expect("unable to create docs");
+        fs::write(
+            tmp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"foo\"\n",
+        )
+        .expect("unable to write pyproject.toml");
+        fs::write(
+            tmp_dir.path().join("docs").join("requirements.txt"),
+            "sphinx\n",
+        )
+        .expect("unable to write docs/requirements.txt");
+
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        assert_eq!(generator.lockfiles().unwrap(), vec![]);
+
+        // only the requirements installed by the part are inventoried
+        fs::write(
+            tmp_dir.path().join("requirements.txt"),
+            "requests==2.31.0\n",
+        )
+        .expect("unable to write requirements.txt");
+        let generator = PythonProvider::provide(tmp_dir.path(), &Options::default())
+            .expect("unable to provide python generator");
+        assert_eq!(
+            generator.lockfiles().unwrap(),
+            vec![DependencyLock {
+                path: tmp_dir.path().join("requirements.txt"),
+                linked_packages: None,
+            }]
+        );
+    }
 }
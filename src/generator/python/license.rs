@@ -0,0 +1,94 @@
+use crate::generator::LICENSE_CACHE;
+use crate::Result;
+use askalono::Store;
+
+/// The trove classifiers of the common licenses, and their SPDX identifier
+const LICENSE_CLASSIFIERS: [(&str, &str); 9] = [
+    ("Apache Software License", "Apache-2.0"),
+    ("GNU General Public License v2 (GPLv2)", "GPL-2.0-only"),
+    ("GNU General Public License v3 (GPLv3)", "GPL-3.0-only"),
+    (
+        "GNU Lesser General Public License v3 (LGPLv3)",
+        "LGPL-3.0-only",
+    ),
+    ("ISC License (ISCL)", "ISC"),
+    ("MIT License", "MIT"),
+    ("Mozilla Public License 2.0 (MPL 2.0)", "MPL-2.0"),
+    ("Python Software Foundation License", "PSF-2.0"),
+    ("The Unlicense (Unlicense)", "Unlicense"),
+];
+
+/// Check whether given license is a valid SPDX expression (i.e `MIT OR Apache-2.0`),
+/// made of licenses known by the license store.
+pub fn is_spdx_expression(license: &str) -> Result<bool> {
+    let store = Store::from_cache(LICENSE_CACHE)?;
+    let is_known = |id: &str| {
+        let id = id.strip_suffix('+').unwrap_or(id);
+        // the store only has the `-only` variant of the GNU licenses (i.e GPL-3.0-or-later)
+        let only = format!("{}-only", id.strip_suffix("-or-later").unwrap_or(id));
+        id.starts_with("LicenseRef-") || store.licenses().any(|l| *l == id || *l == only)
+    };
+
+    // licenses (optionally followed by `WITH <exception>`) separated by AND/OR operators
+    let expression = license.replace('(', " ( ").replace(')', " ) ");
+    let mut tokens = expression.split_whitespace();
+    let mut expect_license = true;
+    let mut depth = 0;
+    while let Some(token) = tokens.next() {
+        match (expect_license, token) {
+            (true, "(") => depth += 1,
+            (true, _) if is_known(token) => expect_license = false,
+            (false, ")") if depth > 0 => depth -= 1,
+            (false, "AND") | (false, "OR") => expect_license = true,
+            // the exceptions (i.e `LLVM-exception`) are not part of the store
+            (false, "WITH") if !matches!(tokens.next(), None | Some("(") | Some(")")) => {}
+            _ => return Ok(false),
+        }
+    }
+
+    Ok(!expect_license && depth == 0)
+}
+
+/// The SPDX identifier of given trove license classifier, if known
+/// (i.e `License :: OSI Approved :: MIT License`)
+pub fn classifier_license(classifier: &str) -> Option<String> {
+    let classifier = classifier
+        .trim()
+        .strip_prefix("License :: OSI Approved :: ")?;
+    LICENSE_CLASSIFIERS
+        .iter()
+        .find(|(c, _)| *c == classifier)
+        .map(|(_, license)| license.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_spdx_expression() {
+        for license in [
+            "MIT",
+            "GPL-3.0-or-later",
+            "(MIT OR Apache-2.0) AND BSD-3-Clause",
+            "Apache-2.0 WITH LLVM-exception",
+        ] {
+            assert!(is_spdx_expression(license).unwrap(), "{}", license);
+        }
+        for license in ["BSD", "MIT License", "see LICENSE", "MIT OR", "(MIT", ""] {
+            assert!(!is_spdx_expression(license).unwrap(), "{}", license);
+        }
+    }
+
+    #[test]
+    fn test_classifier_license() {
+        assert_eq!(
+            classifier_license("License :: OSI Approved :: MIT License"),
+            Some("MIT".to_string())
+        );
+        assert_eq!(
+            classifier_license("License :: Other/Proprietary License"),
+            None
+        );
+    }
+}
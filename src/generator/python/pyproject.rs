@@ -0,0 +1,244 @@
+use crate::generator::python::setup_py::{find_file_attr, find_module_attr};
+use crate::generator::python::{requirement_name, Metadata};
+use crate::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// The build backend used to build the python package (PEP 517)
+#[derive(Debug, PartialEq, Clone)]
+pub enum BuildBackend {
+    Setuptools,
+    Poetry,
+    Flit,
+    Hatch,
+    Pdm,
+    Other(String),
+}
+
+impl From<&str> for BuildBackend {
+    fn from(s: &str) -> Self {
+        match s {
+            "setuptools.build_meta" | "setuptools.build_meta:__legacy__" => {
+                BuildBackend::Setuptools
+            }
+            "poetry.core.masonry.api" | "poetry.masonry.api" => BuildBackend::Poetry,
+            "flit_core.buildapi" | "flit.buildapi" => BuildBackend::Flit,
+            "hatchling.build" => BuildBackend::Hatch,
+            "pdm.backend" | "pdm.pep517.api" => BuildBackend::Pdm,
+            backend => BuildBackend::Other(backend.to_string()),
+        }
+    }
+}
+
+/// The pyproject.toml file
+pub struct PyProject {
+    source_path: PathBuf,
+    content: Value,
+}
+
+impl PyProject {
+    /// Load the pyproject.toml located in given source, if any.
+    pub fn load<P: AsRef<Path>>(source_path: P) -> Result<Option<PyProject>> {
+        let path = source_path.as_ref().join("pyproject.toml");
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(Some(PyProject {
+            source_path: source_path.as_ref().to_path_buf(),
+            content: toml::from_str(&content)?,
+        }))
+    }
+
+    /// The build backend declared in [build-system] (setuptools by default)
+    pub fn build_backend(&self) -> BuildBackend {
+        self.get(&["build-system", "build-backend"])
+            .and_then(|v| v.as_str())
+            .map(BuildBackend::from)
+            .unwrap_or(BuildBackend::Setuptools)
+    }
+
+    /// The requirements needed to build the package
+    pub fn build_requires(&self) -> Vec<String> {
+        strings(self.get(&["build-system", "requires"]))
+    }
+
+    /// Extract the package metadata from the PEP 621 [project] table,
+    /// or the [tool.poetry] one for older Poetry projects.
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata::default();
+
+        if let Some(project) = self.get(&["project"]) {
+            metadata.name = string(project.get("name"));
+            metadata.version = string(project.get("version")).or_else(|| self.dynamic_version());
+            metadata.description = string(project.get("description"));
+            metadata.license = match project.get("license") {
+                Some(Value::String(license)) => Some(license.clone()),
+                Some(Value::Table(license)) => string(license.get("text")),
+                _ => None,
+            };
+            metadata.classifiers = strings(project.get("classifiers"));
+            metadata.scripts = keys(project.get("scripts"));
+            metadata.scripts.extend(keys(project.get("gui-scripts")));
+            metadata.requirements = strings(project.get("dependencies"))
+                .iter()
+                .filter_map(|r| requirement_name(r))
+                .collect();
+            metadata.requires_python = string(project.get("requires-python"));
+        }
+
+        if let Some(poetry) = self.get(&["tool", "poetry"]) {
+            metadata.merge(Metadata {
+                name: string(poetry.get("name")),
+                version: string(poetry.get("version")),
+                description: string(poetry.get("description")),
+                license: string(poetry.get("license")),
+                classifiers: strings(poetry.get("classifiers")),
+                scripts: keys(poetry.get("scripts")),
+                requirements: keys(poetry.get("dependencies"))
+                    .iter()
+                    .filter(|d| *d != "python")
+                    .filter_map(|r| requirement_name(r))
+                    .collect(),
+                requires_python: string(self.get(&["tool", "poetry", "dependencies", "python"])),
+            });
+        }
+
+        metadata
+    }
+
+    // resolve the project version when declared as dynamic
+    fn dynamic_version(&self) -> Option<String> {
+        // setuptools: version = {attr = "foo.__version__"} or {file = "VERSION"}
+        if let Some(version) = self.get(&["tool", "setuptools", "dynamic", "version"]) {
+            if let Some(attr) = version.get("attr").and_then(|v| v.as_str()) {
+                return resolve_attr(&self.source_path, attr);
+            }
+            if let Some(file) = version.get("file").and_then(|v| v.as_str()) {
+                return read_version_file(self.source_path.join(file));
+            }
+        }
+
+        // hatch: [tool.hatch.version] path = "src/foo/__about__.py"
+        if let Some(path) = self
+            .get(&["tool", "hatch", "version", "path"])
+            .and_then(|v| v.as_str())
+        {
+            return find_file_attr(self.source_path.join(path), "__version__");
+        }
+
+        // flit (and others): the package module __version__
+        let name = self.get(&["project", "name"]).and_then(|v| v.as_str())?;
+        find_module_attr(
+            &self.source_path,
+            &name.to_lowercase().replace('-', "_"),
+            "__version__",
+        )
+    }
+
+    fn get(&self, keys: &[&str]) -> Option<&Value> {
+        let mut value = &self.content;
+        for key in keys {
+            value = value.get(key)?;
+        }
+        Some(value)
+    }
+}
+
+/// Resolve an `attr:` directive (i.e `foo.__version__`)
+pub fn resolve_attr<P: AsRef<Path>>(source_path: P, attr: &str) -> Option<String> {
+    let (module, attr) = attr.trim().rsplit_once('.')?;
+    find_module_attr(source_path, module, attr)
+}
+
+/// Read a `file:` version directive
+pub fn read_version_file<P: AsRef<Path>>(path: P) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn string(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn keys(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_table())
+        .map(|table| table.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|v| v.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_pep621_metadata() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src").join("foo"))
+            .expect("unable to create src/foo");
+        fs::write(
+            tmp_dir.path().join("pyproject.toml"),
+            "[build-system]\nrequires = [\"hatchling\", \"hatch-vcs\"]\nbuild-backend = \"hatchling.build\"\n\n[project]\nname = \"foo\"\ndescription = \"A foo tool\"\nlicense = {text = \"Apache-2.0\"}\ndynamic = [\"version\"]\n\n[tool.hatch.version]\npath = \"src/foo/__about__.py\"\n",
+        )
+        .expect("unable to write pyproject.toml");
+        fs::write(
+            tmp_dir.path().join("src").join("foo").join("__about__.py"),
+            "__version__ = \"2.0.1\"\n",
+        )
+        .expect("unable to write src/foo/__about__.py");
+
+        let pyproject = PyProject::load(tmp_dir.path())
+            .expect("unable to load pyproject.toml")
+            .expect("pyproject.toml not found");
+        assert_eq!(pyproject.build_backend(), BuildBackend::Hatch);
+        assert_eq!(pyproject.build_requires(), vec!["hatchling", "hatch-vcs"]);
+
+        let metadata = pyproject.metadata();
+        assert_eq!(metadata.name, Some("foo".to_string()));
+        assert_eq!(metadata.version, Some("2.0.1".to_string()));
+        assert_eq!(metadata.description, Some("A foo tool".to_string()));
+        assert_eq!(metadata.license, Some("Apache-2.0".to_string()));
+    }
+
+    #[test]
+    fn test_poetry_metadata() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join("pyproject.toml"),
+            "[tool.poetry]\nname = \"bar\"\nversion = \"0.1.0\"\ndescription = \"A bar tool\"\nlicense = \"MIT\"\n\n[build-system]\nrequires = [\"poetry-core\"]\nbuild-backend = \"poetry.core.masonry.api\"\n",
+        )
+        .expect("unable to write pyproject.toml");
+
+        let pyproject = PyProject::load(tmp_dir.path())
+            .expect("unable to load pyproject.toml")
+            .expect("pyproject.toml not found");
+        assert_eq!(pyproject.build_backend(), BuildBackend::Poetry);
+
+        let metadata = pyproject.metadata();
+        assert_eq!(metadata.name, Some("bar".to_string()));
+        assert_eq!(metadata.version, Some("0.1.0".to_string()));
+        assert_eq!(metadata.license, Some("MIT".to_string()));
+    }
+}
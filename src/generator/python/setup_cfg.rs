@@ -0,0 +1,164 @@
+use crate::generator::python::pyproject::{read_version_file, resolve_attr};
+use crate::generator::python::{entry_point_name, requirement_name, Metadata};
+use crate::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The setup.cfg file (declarative setuptools configuration)
+pub struct SetupCfg {
+    source_path: PathBuf,
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl SetupCfg {
+    /// Load the setup.cfg located in given source, if any.
+    pub fn load<P: AsRef<Path>>(source_path: P) -> Result<Option<SetupCfg>> {
+        let path = source_path.as_ref().join("setup.cfg");
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(Some(SetupCfg {
+            source_path: source_path.as_ref().to_path_buf(),
+            sections: parse_ini(&content),
+        }))
+    }
+
+    /// Extract the package metadata from the [metadata] section.
+    pub fn metadata(&self) -> Metadata {
+        let version = self.get("metadata", "version").and_then(|version| {
+            if let Some(attr) = version.strip_prefix("attr:") {
+                resolve_attr(&self.source_path, attr)
+            } else if let Some(file) = version.strip_prefix("file:") {
+                read_version_file(self.source_path.join(file.trim()))
+            } else {
+                Some(version)
+            }
+        });
+
+        Metadata {
+            name: self.get("metadata", "name"),
+            version,
+            description: self.get("metadata", "description"),
+            license: self.get("metadata", "license"),
+            classifiers: self
+                .get("metadata", "classifiers")
+                .map(|v| v.lines().map(|c| c.to_string()).collect())
+                .unwrap_or_default(),
+            scripts: self.scripts(),
+            requirements: self
+                .get("options", "install_requires")
+                .map(|v| v.split(['\n', ';']).filter_map(requirement_name).collect())
+                .unwrap_or_default(),
+            requires_python: self.get("options", "python_requires"),
+        }
+    }
+
+    /// The console/gui entry points and scripts files installed by the package
+    pub fn scripts(&self) -> Vec<String> {
+        let mut scripts: Vec<String> = ["console_scripts", "gui_scripts"]
+            .iter()
+            .filter_map(|group| self.get("options.entry_points", group))
+            .flat_map(|v| {
+                v.lines()
+                    .filter_map(entry_point_name)
+                    .collect::<Vec<String>>()
+            })
+            .collect();
+
+        if let Some(files) = self.get("options", "scripts") {
+            scripts.extend(files.split_whitespace().filter_map(|f| {
+                Path::new(f)
+                    .file_name()
+                    .map(|f| f.to_string_lossy().to_string())
+            }));
+        }
+
+        scripts
+    }
+
+    /// Get the value of given key in given section
+    pub fn get(&self, section: &str, key: &str) -> Option<String> {
+        self.sections
+            .get(section)
+            .and_then(|s| s.get(key))
+            .cloned()
+            .filter(|v| !v.is_empty())
+    }
+}
+
+// parse an INI file, multi-lines values (indented continuation lines) are joined using \n
+fn parse_ini(content: &str) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut sections: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut section = String::new();
+    let mut key: Option<String> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            section = trimmed[1..trimmed.len() - 1].trim().to_string();
+            key = None;
+        } else if line.starts_with(char::is_whitespace) {
+            // continuation line
+            if let (Some(key), false) = (&key, trimmed.is_empty()) {
+                if let Some(value) = sections.entry(section.clone()).or_default().get_mut(key) {
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(trimmed);
+                }
+            }
+        } else if let Some(pos) = trimmed.find(['=', ':']) {
+            let k = trimmed[..pos].trim().to_string();
+            let v = trimmed[pos + 1..].trim().to_string();
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(k.clone(), v);
+            key = Some(k);
+        }
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_metadata() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("foo")).expect("unable to create foo");
+        fs::write(
+            tmp_dir.path().join("setup.cfg"),
+            "[metadata]\nname = foo\nversion = attr: foo.__version__\ndescription = A foo tool\nlicense = BSD-3-Clause\n\n[options]\ninstall_requires =\n    requests\n    click>=7\n",
+        )
+        .expect("unable to write setup.cfg");
+        fs::write(
+            tmp_dir.path().join("foo").join("__init__.py"),
+            "__version__ = '1.0.0'\n",
+        )
+        .expect("unable to write foo/__init__.py");
+
+        let setup_cfg = SetupCfg::load(tmp_dir.path())
+            .expect("unable to load setup.cfg")
+            .expect("setup.cfg not found");
+        let metadata = setup_cfg.metadata();
+        assert_eq!(metadata.name, Some("foo".to_string()));
+        assert_eq!(metadata.version, Some("1.0.0".to_string()));
+        assert_eq!(metadata.description, Some("A foo tool".to_string()));
+        assert_eq!(metadata.license, Some("BSD-3-Clause".to_string()));
+        assert_eq!(
+            setup_cfg.get("options", "install_requires"),
+            Some("requests\nclick>=7".to_string())
+        );
+    }
+}
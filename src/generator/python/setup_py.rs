@@ -0,0 +1,783 @@
+use crate::generator::python::entry_point_name;
+use crate::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A (simplified) python value, as found in a setup.py file
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Str(String),
+    Num(String),
+    List(Vec<Value>),
+    Dict(Vec<(Value, Value)>),
+    /// Anything that cannot be statically evaluated (function calls, attributes, ...)
+    Unknown,
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<String> {
+        self.as_str().map(|s| s.to_string())
+    }
+
+    /// Lookup given key in a dict value
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Dict(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Return the strings contained in a list value (unknown entries are skipped)
+    pub fn as_strings(&self) -> Vec<String> {
+        match self {
+            Value::List(values) => values.iter().filter_map(|v| v.as_string()).collect(),
+            Value::Str(s) => vec![s.clone()],
+            _ => vec![],
+        }
+    }
+}
+
+/// The setup.py file, statically parsed (i.e without executing it).
+#[derive(Debug, Default, PartialEq)]
+pub struct SetupPy {
+    /// The keyword arguments given to the setup() call
+    pub kwargs: BTreeMap<String, Value>,
+}
+
+impl SetupPy {
+    /// Load the setup.py located in given source, evaluating the setup() keyword arguments
+    /// using literal values and module level constants only. If the version cannot be
+    /// determinate this way, try to find the `__version__` constant of the package.
+    pub fn load<P: AsRef<Path>>(source_path: P) -> Result<SetupPy> {
+        let content = fs::read_to_string(source_path.as_ref().join("setup.py"))?;
+        let module = parse_module(&content);
+
+        let mut setup_py = SetupPy {
+            kwargs: module.setup_kwargs.unwrap_or_default(),
+        };
+
+        if setup_py.get_str("version").is_none() {
+            if let Some(version) = find_package_version(&source_path, &setup_py) {
+                log::debug!("Found package version ({}) using __version__", version);
+                setup_py
+                    .kwargs
+                    .insert("version".to_string(), Value::Str(version));
+            }
+        }
+
+        Ok(setup_py)
+    }
+
+    /// Get the string value of given keyword argument, if statically known
+    pub fn get_str(&self, key: &str) -> Option<String> {
+        self.kwargs
+            .get(key)
+            .and_then(|v| v.as_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Get given keyword argument
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.kwargs.get(key)
+    }
+
+    /// The scripts installed by the package: console/gui entry points and scripts files
+    pub fn scripts(&self) -> Vec<String> {
+        let mut scripts = Vec::new();
+
+        if let Some(entry_points) = self.get("entry_points") {
+            for group in ["console_scripts", "gui_scripts"].iter() {
+                let declarations = match entry_points {
+                    // entry points can also be declared using the INI format
+                    Value::Str(ini) => ini_group(ini, group),
+                    _ => entry_points
+                        .get(group)
+                        .map(|v| v.as_strings())
+                        .unwrap_or_default(),
+                };
+                scripts.extend(declarations.iter().filter_map(|d| entry_point_name(d)));
+            }
+        }
+
+        if let Some(files) = self.get("scripts") {
+            scripts.extend(files.as_strings().iter().filter_map(|f| {
+                Path::new(f)
+                    .file_name()
+                    .map(|f| f.to_string_lossy().to_string())
+            }));
+        }
+
+        scripts
+    }
+}
+
+// extract the entries of given [group] from an INI entry_points declaration
+fn ini_group(ini: &str, group: &str) -> Vec<String> {
+    let header = format!("[{}]", group);
+    ini.lines()
+        .map(|l| l.trim())
+        .skip_while(|l| *l != header)
+        .skip(1)
+        .take_while(|l| !l.starts_with('['))
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect()
+}
+
+// try to find `__version__` in the package files
+fn find_package_version<P: AsRef<Path>>(source_path: P, setup_py: &SetupPy) -> Option<String> {
+    let mut packages: Vec<String> = Vec::new();
+    if let Some(packages_value) = setup_py.get("packages") {
+        packages.extend(packages_value.as_strings());
+    }
+    if let Some(modules) = setup_py.get("py_modules") {
+        packages.extend(modules.as_strings());
+    }
+    if let Some(name) = setup_py.get_str("name") {
+        packages.push(name.to_lowercase().replace('-', "_"));
+    }
+
+    for package in packages {
+        for module in ["", ".__version__", "._version", ".version"].iter() {
+            let module = format!("{}{}", package, module);
+            if let Some(version) = find_module_attr(&source_path, &module, "__version__") {
+                return Some(version);
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the string value of given attribute (i.e `__version__`) of given module (i.e `foo.bar`),
+/// looking in both the source root and the `src` directory.
+pub fn find_module_attr<P: AsRef<Path>>(
+    source_path: P,
+    module: &str,
+    attr: &str,
+) -> Option<String> {
+    let module_path = module.replace('.', "/");
+    for root in ["", "src"].iter() {
+        let path = source_path.as_ref().join(root).join(&module_path);
+        for candidate in [path.join("__init__.py"), path.with_extension("py")].iter() {
+            if let Some(value) = find_file_attr(candidate, attr) {
+                return Some(value);
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the string value of given attribute defined in given python file.
+pub fn find_file_attr<P: AsRef<Path>>(path: P, attr: &str) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    match parse_module(&content).constants.get(attr) {
+        Some(Value::Str(value)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// The result of parsing a python module
+#[derive(Debug, Default)]
+pub struct Module {
+    /// The statically known assignments (NAME = literal)
+    pub constants: BTreeMap<String, Value>,
+    /// The setup() call keyword arguments, if any
+    pub setup_kwargs: Option<BTreeMap<String, Value>>,
+}
+
+/// Parse given python source code.
+pub fn parse_module(content: &str) -> Module {
+    let tokens = tokenize(content);
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        module: Module::default(),
+    };
+    parser.parse();
+    parser.module
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Str(String),
+    /// f-strings cannot be evaluated
+    FStr,
+    Num(String),
+    Name(String),
+    Op(String),
+    Newline,
+}
+
+const OPERATORS: [&str; 24] = [
+    "**=", "//=", "...", "**", "//", "==", "!=", "<=", ">=", "->", ":=", "+=", "-=", "*=", "/=",
+    "%=", "&=", "|=", "^=", "<<", ">>", "@=", "<>", "~",
+];
+
+fn tokenize(content: &str) -> Vec<Token> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut tokens = Vec::new();
+    let mut depth = 0;
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '\\' && chars.get(i + 1) == Some(&'\n') {
+            i += 2;
+        } else if c == '\n' {
+            if depth == 0 && tokens.last().map(|t| *t != Token::Newline).unwrap_or(false) {
+                tokens.push(Token::Newline);
+            }
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if let Some((token, end)) = read_string(&chars, i) {
+            tokens.push(token);
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit()
+            || (c == '.'
+                && chars
+                    .get(i + 1)
+                    .map(|c| c.is_ascii_digit())
+                    .unwrap_or(false))
+        {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '.' || chars[i] == '_')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Num(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .map(|op| op.to_string())
+                .unwrap_or_else(|| c.to_string());
+            match op.as_str() {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" => depth = (depth - 1).max(0),
+                _ => {}
+            }
+            i += op.chars().count();
+            tokens.push(Token::Op(op));
+        }
+    }
+
+    tokens
+}
+
+// read a (possibly prefixed) string literal starting at given position
+fn read_string(chars: &[char], start: usize) -> Option<(Token, usize)> {
+    let mut i = start;
+    let mut prefix = String::new();
+    while i < chars.len() && i - start < 2 && "rRbBuUfF".contains(chars[i]) {
+        prefix.push(chars[i].to_ascii_lowercase());
+        i += 1;
+    }
+
+    let quote = *chars.get(i)?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let triple = chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote);
+    i += if triple { 3 } else { 1 };
+
+    let raw = prefix.contains('r');
+    let mut value = String::new();
+    while i < chars.len() {
+        let c = chars[i];
+        if c == quote
+            && (!triple || (chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote)))
+        {
+            i += if triple { 3 } else { 1 };
+            let token = if prefix.contains('f') {
+                Token::FStr
+            } else {
+                Token::Str(value)
+            };
+            return Some((token, i));
+        }
+
+        if c == '\\' && i + 1 < chars.len() {
+            let next = chars[i + 1];
+            if raw {
+                value.push(c);
+                value.push(next);
+            } else {
+                match next {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    '\n' => {}
+                    '\\' | '\'' | '"' => value.push(next),
+                    _ => {
+                        value.push(c);
+                        value.push(next);
+                    }
+                }
+            }
+            i += 2;
+        } else {
+            value.push(c);
+            i += 1;
+        }
+    }
+
+    // unterminated string
+    Some((Token::FStr, i))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    module: Module,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        matches!(self.peek(), Some(Token::Op(o)) if o == op)
+    }
+
+    fn is_name(&self, name: &str) -> bool {
+        matches!(self.peek(), Some(Token::Name(n)) if n == name)
+    }
+
+    fn parse(&mut self) {
+        let mut statement_start = true;
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Newline => {
+                    statement_start = true;
+                    self.pos += 1;
+                    continue;
+                }
+                // NAME = value
+                Token::Name(name)
+                    if statement_start
+                        && matches!(self.peek_at(1), Some(Token::Op(o)) if o == "=") =>
+                {
+                    self.pos += 2;
+                    let value = self.parse_expr();
+                    if value != Value::Unknown || !self.module.constants.contains_key(&name) {
+                        self.module.constants.insert(name, value);
+                    }
+                }
+                // setup(...) / setuptools.setup(...)
+                Token::Name(name)
+                    if name == "setup"
+                        && matches!(self.peek_at(1), Some(Token::Op(o)) if o == "(")
+                        && !matches!(
+                            self.pos.checked_sub(1).and_then(|p| self.tokens.get(p)),
+                            Some(Token::Name(n)) if n == "def"
+                        ) =>
+                {
+                    self.pos += 2;
+                    let (_, kwargs) = self.parse_call_args();
+                    self.module.setup_kwargs = Some(kwargs);
+                }
+                _ => self.pos += 1,
+            }
+            statement_start = false;
+        }
+    }
+
+    // parse call arguments, the opening parenthesis being already consumed
+    fn parse_call_args(&mut self) -> (Vec<Value>, BTreeMap<String, Value>) {
+        let mut args = Vec::new();
+        let mut kwargs = BTreeMap::new();
+
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Op(op) if op == ")" => {
+                    self.pos += 1;
+                    break;
+                }
+                Token::Op(op) if op == "," => self.pos += 1,
+                Token::Name(name) if matches!(self.peek_at(1), Some(Token::Op(o)) if o == "=") => {
+                    self.pos += 2;
+                    let value = self.parse_expr();
+                    kwargs.insert(name, value);
+                }
+                _ => {
+                    let before = self.pos;
+                    args.push(self.parse_expr());
+                    if self.pos == before {
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+
+        (args, kwargs)
+    }
+
+    fn parse_expr(&mut self) -> Value {
+        let value = self.parse_binary();
+
+        // conditional expressions, comprehensions, lambda, ...
+        if self.is_name("if") || self.is_name("for") || self.is_op(":=") {
+            self.skip_expr();
+            return Value::Unknown;
+        }
+
+        value
+    }
+
+    fn parse_binary(&mut self) -> Value {
+        let mut value = self.parse_unary();
+
+        loop {
+            let is_operator = match self.peek() {
+                Some(Token::Op(op)) => [
+                    "+", "-", "*", "/", "//", "%", "**", "|", "&", "^", "<<", ">>", "==", "!=",
+                    "<", ">", "<=", ">=", "@",
+                ]
+                .contains(&op.as_str()),
+                Some(Token::Name(n)) => ["and", "or", "in", "not", "is"].contains(&n.as_str()),
+                _ => false,
+            };
+            if !is_operator {
+                return value;
+            }
+
+            let concat = self.is_op("+");
+            self.pos += 1;
+            // `not in` / `is not`
+            if self.is_name("not") || self.is_name("in") {
+                self.pos += 1;
+            }
+            let right = self.parse_unary();
+
+            value = match (value, right) {
+                (Value::Str(l), Value::Str(r)) if concat => Value::Str(l + &r),
+                (Value::List(mut l), Value::List(r)) if concat => {
+                    l.extend(r);
+                    Value::List(l)
+                }
+                _ => Value::Unknown,
+            };
+        }
+    }
+
+    fn parse_unary(&mut self) -> Value {
+        if self.is_op("-")
+            || self.is_op("+")
+            || self.is_op("*")
+            || self.is_op("**")
+            || self.is_op("~")
+            || self.is_name("not")
+            || self.is_name("await")
+        {
+            self.pos += 1;
+            self.parse_unary();
+            return Value::Unknown;
+        }
+        if self.is_name("lambda") {
+            self.skip_expr();
+            return Value::Unknown;
+        }
+
+        let mut value = self.parse_primary();
+
+        // attribute access, calls and subscripts
+        loop {
+            if self.is_op(".") {
+                self.pos += 2;
+                value = Value::Unknown;
+            } else if self.is_op("(") {
+                self.pos += 1;
+                self.parse_call_args();
+                value = Value::Unknown;
+            } else if self.is_op("[") {
+                self.pos += 1;
+                self.parse_sequence("]");
+                value = Value::Unknown;
+            } else {
+                return value;
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Value {
+        let token = match self.peek().cloned() {
+            Some(token) => token,
+            None => return Value::Unknown,
+        };
+
+        match token {
+            Token::Str(_) | Token::FStr => {
+                // implicit concatenation of adjacent strings
+                let mut result = Some(String::new());
+                while let Some(token) = self.peek().cloned() {
+                    match token {
+                        Token::Str(s) => {
+                            if let Some(r) = result.as_mut() {
+                                r.push_str(&s)
+                            }
+                        }
+                        Token::FStr => result = None,
+                        _ => break,
+                    }
+                    self.pos += 1;
+                }
+                result.map(Value::Str).unwrap_or(Value::Unknown)
+            }
+            Token::Num(n) => {
+                self.pos += 1;
+                Value::Num(n)
+            }
+            Token::Name(name) => {
+                self.pos += 1;
+                self.module
+                    .constants
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or(Value::Unknown)
+            }
+            Token::Op(op) if op == "[" => {
+                self.pos += 1;
+                Value::List(self.parse_sequence("]"))
+            }
+            Token::Op(op) if op == "(" => {
+                self.pos += 1;
+                let values = self.parse_sequence(")");
+                // a parenthesized expression is not a tuple
+                if values.len() == 1
+                    && !matches!(self.tokens.get(self.pos - 2), Some(Token::Op(o)) if o == ",")
+                {
+                    values.into_iter().next().unwrap_or(Value::Unknown)
+                } else {
+                    Value::List(values)
+                }
+            }
+            Token::Op(op) if op == "{" => {
+                self.pos += 1;
+                self.parse_dict()
+            }
+            _ => Value::Unknown,
+        }
+    }
+
+    // parse comma separated values until given closing token (the opening one being consumed)
+    fn parse_sequence(&mut self, closing: &str) -> Vec<Value> {
+        let mut values = Vec::new();
+        let mut known = true;
+
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Op(op) if op == closing => {
+                    self.pos += 1;
+                    break;
+                }
+                Token::Op(op) if op == "," || op == ":" => self.pos += 1,
+                Token::Newline => self.pos += 1,
+                _ => {
+                    // *unpacking makes the sequence partially unknown
+                    if self.is_op("*") {
+                        known = false;
+                    }
+                    let before = self.pos;
+                    let value = self.parse_expr();
+                    values.push(value);
+                    if self.pos == before {
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+
+        if !known {
+            values.push(Value::Unknown);
+        }
+        values
+    }
+
+    // parse a dict, the opening brace being already consumed
+    fn parse_dict(&mut self) -> Value {
+        let mut entries = Vec::new();
+        let mut known = true;
+
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Op(op) if op == "}" => {
+                    self.pos += 1;
+                    break;
+                }
+                Token::Op(op) if op == "," => self.pos += 1,
+                Token::Newline => self.pos += 1,
+                _ => {
+                    let before = self.pos;
+                    let key = self.parse_expr();
+                    if self.is_op(":") {
+                        self.pos += 1;
+                        let value = self.parse_expr();
+                        entries.push((key, value));
+                    } else {
+                        // set literal or **spread
+                        known = false;
+                    }
+                    if self.pos == before {
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+
+        if known {
+            Value::Dict(entries)
+        } else {
+            Value::Unknown
+        }
+    }
+
+    // skip the rest of the current expression
+    fn skip_expr(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Op(op) if op == "(" || op == "[" || op == "{" => depth += 1,
+                Token::Op(op) if op == ")" || op == "]" || op == "}" => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                }
+                Token::Op(op) if op == "," && depth == 0 => return,
+                Token::Newline if depth == 0 => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_parse_module() {
+        let setup_py = r#"
+import os
+from setuptools import setup, find_packages
+
+NAME = "foo-bar"
+DESCRIPTION = ("A tool "
+               'to do things')
+
+def read(path):
+    with open(path) as f:
+        return f.read()
+
+setup(
+    name=NAME,
+    version=read("VERSION").strip(),
+    description=DESCRIPTION,
+    long_description=read("README.md"),
+    license="MIT",  # the license
+    packages=find_packages(exclude=["tests"]),
+    install_requires=["requests>=2.0", "click"] + ["lxml"],
+    entry_points={
+        "console_scripts": [
+            "foo=foo.cli:main",
+        ],
+    },
+    classifiers=[f"Python :: {v}" for v in ["3.6", "3.7"]],
+)
+"#;
+
+        let module = parse_module(setup_py);
+        assert_eq!(
+            module.constants.get("NAME"),
+            Some(&Value::Str("foo-bar".to_string()))
+        );
+
+        let kwargs = module.setup_kwargs.expect("setup() not found");
+        assert_eq!(kwargs.get("name"), Some(&Value::Str("foo-bar".to_string())));
+        assert_eq!(kwargs.get("version"), Some(&Value::Unknown));
+        assert_eq!(
+            kwargs.get("description"),
+            Some(&Value::Str("A tool to do things".to_string()))
+        );
+        assert_eq!(kwargs.get("license"), Some(&Value::Str("MIT".to_string())));
+        assert_eq!(kwargs.get("packages"), Some(&Value::Unknown));
+        assert_eq!(
+            kwargs.get("install_requires").map(|v| v.as_strings()),
+            Some(vec![
+                "requests>=2.0".to_string(),
+                "click".to_string(),
+                "lxml".to_string()
+            ])
+        );
+        assert_eq!(
+            kwargs.get("entry_points"),
+            Some(&Value::Dict(vec![(
+                Value::Str("console_scripts".to_string()),
+                Value::List(vec![Value::Str("foo=foo.cli:main".to_string())])
+            )]))
+        );
+        assert_eq!(
+            kwargs.get("classifiers").map(|v| v.as_strings()),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn test_load_with_package_version() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src").join("foo_bar"))
+            .expect("unable to create src/foo_bar");
+        fs::write(
+            tmp_dir.path().join("setup.py"),
+            "import setuptools\nfrom foo_bar import __version__\nsetuptools.setup(name='foo-bar', version=__version__)\n",
+        )
+        .expect("unable to write setup.py");
+        fs::write(
+            tmp_dir
+                .path()
+                .join("src")
+                .join("foo_bar")
+                .join("__init__.py"),
+            "\"\"\"Foo bar.\"\"\"\n__version__ = '1.2.3'\n",
+        )
+        .expect("unable to write src/foo_bar/__init__.py");
+
+        let setup_py = SetupPy::load(tmp_dir.path()).expect("unable to load setup.py");
+        assert_eq!(setup_py.get_str("name"), Some("foo-bar".to_string()));
+        assert_eq!(setup_py.get_str("version"), Some("1.2.3".to_string()));
+        assert_eq!(setup_py.get_str("description"), None);
+    }
+}
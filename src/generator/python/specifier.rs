@@ -0,0 +1,97 @@
+/// Check if given python version (i.e `3.8`) satisfies given specifier (i.e `>=3.6, <4`),
+/// as found in `python_requires`, `requires-python` or the Poetry python dependency.
+pub fn satisfies(version: &str, specifier: &str) -> bool {
+    let version = parse_version(version);
+
+    // Poetry allows alternatives using `||`
+    specifier.split("||").any(|alternative| {
+        alternative
+            .split(',')
+            .map(|clause| clause.trim())
+            .filter(|clause| !clause.is_empty())
+            .all(|clause| satisfies_clause(&version, clause))
+    })
+}
+
+fn satisfies_clause(version: &[u32], clause: &str) -> bool {
+    let operator_len = clause
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(clause.len());
+    let (operator, expected) = clause.split_at(operator_len);
+    let wildcard = expected.ends_with(".*");
+    let expected = parse_version(expected.trim_end_matches(".*"));
+    if expected.is_empty() {
+        log::warn!("Unable to parse python version specifier `{}`", clause);
+        return true;
+    }
+
+    match operator.trim() {
+        "==" | "===" | "" if wildcard => version.starts_with(&expected),
+        "==" | "===" | "" => compare(version, &expected) == 0,
+        "!=" if wildcard => !version.starts_with(&expected),
+        "!=" => compare(version, &expected) != 0,
+        ">=" => compare(version, &expected) >= 0,
+        "<=" => compare(version, &expected) <= 0,
+        ">" => compare(version, &expected) > 0,
+        "<" => compare(version, &expected) < 0,
+        // ~=3.6 means >=3.6, ==3.*
+        "~=" => {
+            compare(version, &expected) >= 0
+                && version.starts_with(&expected[..expected.len().max(2) - 1])
+        }
+        // Poetry ^3.8 means >=3.8, <4
+        "^" => compare(version, &expected) >= 0 && version.starts_with(&expected[..1]),
+        // Poetry ~3.8 means >=3.8, <3.9
+        "~" => {
+            compare(version, &expected) >= 0
+                && version.starts_with(&expected[..expected.len().min(2)])
+        }
+        operator => {
+            log::warn!("Unsupported python version operator `{}`", operator);
+            true
+        }
+    }
+}
+
+// compare the given versions on major.minor only, since the patch version of the
+// python shipped by the bases is not known
+fn compare(version: &[u32], expected: &[u32]) -> i8 {
+    for i in 0..2 {
+        let left = version.get(i).copied().unwrap_or(0);
+        let right = expected.get(i).copied().unwrap_or(0);
+        if left != right {
+            return if left < right { -1 } else { 1 };
+        }
+    }
+    0
+}
+
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .trim()
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_satisfies() {
+        assert!(satisfies("3.8", ">=3.6"));
+        assert!(satisfies("3.8", ">=3.6, <4"));
+        assert!(!satisfies("3.6", ">=3.7"));
+        assert!(satisfies("3.10", ">=3.9.2"));
+        assert!(!satisfies("3.12", "<3.12"));
+        assert!(!satisfies("3.8", "==2.7.*"));
+        assert!(satisfies("3.8", "!=3.0.*,!=3.1.*"));
+        assert!(satisfies("3.10", "~=3.8"));
+        assert!(!satisfies("3.10", "~=3.8.1"));
+        assert!(satisfies("3.12", "^3.8"));
+        assert!(!satisfies("3.8", "~3.10"));
+        assert!(satisfies("3.10", "~3.10 || ^3.12"));
+        assert!(satisfies("2.7", ">=2.7, <3"));
+    }
+}
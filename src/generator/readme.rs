@@ -0,0 +1,423 @@
+use std::path::Path;
+use std::{fs, io};
+
+/// The maximum length of a snap summary
+pub const SUMMARY_MAX_LEN: usize = 78;
+
+const README_FILES: [&str; 4] = ["README.md", "README.rst", "README.txt", "README"];
+
+/// Find the source README file and return its content along with its name.
+pub fn find_readme<P: AsRef<Path>>(source_path: P) -> io::Result<Option<(String, String)>> {
+    for filename in README_FILES.iter() {
+        let path = source_path.as_ref().join(filename);
+        if path.exists() {
+            return fs::read_to_string(path).map(|v| Some((v, filename.to_string())));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Extract the description from given README content: i.e the first prose paragraph(s)
+/// located after the title, stripped from any badges, images, links or formatting.
+pub fn extract_description(content: &str, filename: &str) -> Option<String> {
+    let rst = filename.ends_with(".rst");
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+    let mut in_directive = false;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        let trimmed = line.trim();
+        let next = lines.get(i + 1).map(|l| l.trim()).unwrap_or("");
+        i += 1;
+
+        // Skip markdown code blocks, which also end the description
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            flush_paragraph(&mut paragraph, &mut paragraphs);
+            if !paragraphs.is_empty() {
+                break;
+            }
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        // Skip rst directives (and their indented content)
+        if rst && trimmed.starts_with("..") {
+            in_directive = true;
+            flush_paragraph(&mut paragraph, &mut paragraphs);
+            continue;
+        }
+        if in_directive {
+            if trimmed.is_empty() || line.starts_with(char::is_whitespace) {
+                continue;
+            }
+            in_directive = false;
+        }
+
+        if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut paragraphs);
+            continue;
+        }
+
+        // Titles: stop at the first heading found after the description
+        let is_heading = trimmed.starts_with('#') || is_underline(trimmed);
+        let is_setext_title = !next.is_empty() && is_underline(next);
+        if is_heading || is_setext_title {
+            flush_paragraph(&mut paragraph, &mut paragraphs);
+            if !paragraphs.is_empty() {
+                break;
+            }
+            if is_setext_title {
+                i += 1;
+            }
+            continue;
+        }
+
+        // Skip lines that are not prose (html, tables, quotes, lists before any paragraph)
+        if trimmed.starts_with('<')
+            || (!rst && trimmed.starts_with('|'))
+            || trimmed.starts_with('>')
+            || (paragraphs.is_empty() && paragraph.is_empty() && is_list_item(trimmed))
+        {
+            flush_paragraph(&mut paragraph, &mut paragraphs);
+            continue;
+        }
+
+        let stripped = strip_formatting(trimmed);
+        if !stripped.is_empty() {
+            paragraph.push(stripped);
+        }
+    }
+    flush_paragraph(&mut paragraph, &mut paragraphs);
+
+    // Drop trailing paragraphs introducing content we have skipped (i.e `Examples:`)
+    while paragraphs.last().map(|p| p.ends_with(':')).unwrap_or(false) {
+        paragraphs.pop();
+    }
+
+    if paragraphs.is_empty() {
+        None
+    } else {
+        Some(paragraphs.join("\n\n"))
+    }
+}
+
+/// Build a summary out of given description: take the first paragraph
+/// and truncate it to `SUMMARY_MAX_LEN` chars on a word boundary.
+pub fn summarize(description: &str) -> String {
+    let first_paragraph = description.split("\n\n").next().unwrap_or("").trim();
+    if first_paragraph.chars().count() <= SUMMARY_MAX_LEN {
+        return first_paragraph.to_string();
+    }
+
+    let mut summary = String::new();
+    for word in first_paragraph.split_whitespace() {
+        let len = summary.chars().count() + word.chars().count();
+        if summary.is_empty() {
+            if len > SUMMARY_MAX_LEN {
+                return word.chars().take(SUMMARY_MAX_LEN).collect();
+            }
+        } else if len + 1 > SUMMARY_MAX_LEN {
+            break;
+        } else {
+            summary.push(' ');
+        }
+        summary.push_str(word);
+    }
+
+    summary
+}
+
+fn flush_paragraph(paragraph: &mut Vec<String>, paragraphs: &mut Vec<String>) {
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph.join(" "));
+        paragraph.clear();
+    }
+}
+
+// a setext/rst underline is a line made of a single repeated punctuation char
+fn is_underline(line: &str) -> bool {
+    let mut chars = line.chars();
+    match chars.next() {
+        Some(c) if "=-~^*+#`'\".:_".contains(c) => line.len() >= 3 && chars.all(|o| o == c),
+        _ => false,
+    }
+}
+
+fn is_list_item(line: &str) -> bool {
+    line.starts_with("- ")
+        || line.starts_with("* ")
+        || line.starts_with("+ ")
+        || line
+            .split_once(". ")
+            .map(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or(false)
+}
+
+/// Strip badges, images, links and markdown/rst formatting from given line.
+fn strip_formatting(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut result = String::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            // markdown image (and badges): ![alt](url)
+            '!' if chars.get(i + 1) == Some(&'[') => match skip_link(&chars, i + 1) {
+                Some((_, end)) => i = end,
+                None => {
+                    result.push('!');
+                    i += 1;
+                }
+            },
+            // markdown link: [text](url) or [text][ref]
+            '[' => match skip_link(&chars, i) {
+                Some((text, end)) => {
+                    result.push_str(&strip_formatting(&text));
+                    i = end;
+                }
+                None => {
+                    result.push('[');
+                    i += 1;
+                }
+            },
+            // autolink or html tag
+            '<' if is_tag_start(&chars, i) => match chars[i..].iter().position(|c| *c == '>') {
+                Some(end) => i += end + 1,
+                None => {
+                    result.push('<');
+                    i += 1;
+                }
+            },
+            // rst substitution (badges): |name|
+            '|' => match substitution_len(&chars, i) {
+                Some(len) => i += len,
+                None => {
+                    result.push('|');
+                    i += 1;
+                }
+            },
+            // inline code / rst links: `text <url>`_
+            '`' => {
+                let start = i + chars[i..].iter().take_while(|c| **c == '`').count();
+                match chars[start..].iter().position(|c| *c == '`') {
+                    Some(len) => {
+                        let text: String = chars[start..start + len].iter().collect();
+                        let text = match text.find(" <") {
+                            Some(pos) if text.ends_with('>') => text[..pos].to_string(),
+                            _ => text,
+                        };
+                        result.push_str(&text);
+                        i = start + len;
+                        while i < chars.len() && (chars[i] == '`' || chars[i] == '_') {
+                            i += 1;
+                        }
+                    }
+                    None => i = start,
+                }
+            }
+            // emphasis
+            '*' => {
+                let len = chars[i..].iter().take_while(|c| **c == '*').count();
+                if !is_asterisk_emphasis(&chars, i, len) {
+                    result.extend(&chars[i..i + len]);
+                }
+                i += len;
+            }
+            '_' if is_emphasis_boundary(&chars, i) => i += 1,
+            c => {
+                result.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    result.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// Parse a markdown link starting at given `[` position and return its text
+// and the position right after it
+fn skip_link(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut depth = 0;
+    let mut text_end = None;
+    for (pos, c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    text_end = Some(pos);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let text_end = text_end?;
+    let text: String = chars[start + 1..text_end].iter().collect();
+
+    let closing = match chars.get(text_end + 1) {
+        Some('(') => ')',
+        Some('[') => ']',
+        _ => return None,
+    };
+    let target_end = chars[text_end + 2..].iter().position(|c| *c == closing)?;
+
+    Some((text, text_end + 2 + target_end + 1))
+}
+
+// whether the `<` at given position opens an html tag (i.e `<a `, `</p>`, `<br>`)
+// or an autolink (i.e `<https://...>`), and not a comparison (i.e `a < b`)
+fn is_tag_start(chars: &[char], start: usize) -> bool {
+    let rest: String = chars[start + 1..].iter().take(8).collect();
+    let rest = rest.to_lowercase();
+    if rest.starts_with("http://") || rest.starts_with("https:/") || rest.starts_with('/') {
+        return true;
+    }
+    let name_len = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .count();
+    name_len > 0
+        && rest
+            .chars()
+            .next()
+            .map(|c| c.is_ascii_alphabetic())
+            .unwrap_or(false)
+        && matches!(
+            rest.chars().nth(name_len),
+            Some(' ') | Some('>') | Some('/')
+        )
+}
+
+// the length of the rst substitution (i.e `|build|`) starting at given position, if any
+fn substitution_len(chars: &[char], start: usize) -> Option<usize> {
+    let len = chars[start + 1..].iter().position(|c| *c == '|')?;
+    let name = &chars[start + 1..start + 1 + len];
+    if name.is_empty() || name.iter().any(|c| c.is_whitespace()) {
+        None
+    } else {
+        Some(len + 2)
+    }
+}
+
+// a run of `*` is emphasis when it opens (i.e `**bold`) or closes (i.e `bold**`) a word,
+// but not when surrounded by spaces or words (i.e `2 * 3` or `2*3`)
+fn is_asterisk_emphasis(chars: &[char], start: usize, len: usize) -> bool {
+    let before = start.checked_sub(1).map(|pos| chars[pos]);
+    let after = chars.get(start + len).copied();
+    let is_boundary = |c: Option<char>| c.map(|c| !c.is_alphanumeric()).unwrap_or(true);
+    let is_word = |c: Option<char>| c.map(|c| !c.is_whitespace()).unwrap_or(false);
+
+    (is_boundary(before) && is_word(after)) || (is_word(before) && is_boundary(after))
+}
+
+// `_` is only considered as emphasis when not surrounded by alphanumeric chars (i.e snake_case)
+fn is_emphasis_boundary(chars: &[char], pos: usize) -> bool {
+    let before = pos > 0 && chars[pos - 1].is_alphanumeric();
+    let after = chars
+        .get(pos + 1)
+        .map(|c| c.is_alphanumeric())
+        .unwrap_or(false);
+    !(before && after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_description_markdown() {
+        let readme = "# Autosnap
+
+![Crates.io](https://img.shields.io/crates/v/autosnap) [![CI](https://ci/badge.svg)](https://ci)
+
+Automatically make **Snap** package from [source code](https://github.com).
+It supports `Rust`, Go and python_projects.
+
+Examples:
+
+```sh
+$ autosnap .
+```
+
+# How to install
+";
+
+        assert_eq!(
+            extract_description(readme, "README.md"),
+            Some(
+                "Automatically make Snap package from source code. It supports Rust, Go and python_projects."
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_extract_description_rst() {
+        let readme = "=======
+Project
+=======
+
+.. image:: https://badge.svg
+   :target: https://ci
+
+|build| A `small <https://example.org>`_ tool to do ``things``.
+
+Usage
+-----
+
+Run it.
+";
+
+        assert_eq!(
+            extract_description(readme, "README.rst"),
+            Some("A small tool to do things.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_strip_formatting() {
+        assert_eq!(
+            strip_formatting("Compute *a* | **b** with 2 * 3 or 2*3"),
+            "Compute a | b with 2 * 3 or 2*3"
+        );
+        assert_eq!(strip_formatting("|build| |coverage| Done"), "Done");
+        assert_eq!(
+            strip_formatting(
+                "Keep a < b and c > d, drop <a href=\"x\">tags</a> and <https://x.org>"
+            ),
+            "Keep a < b and c > d, drop tags and"
+        );
+    }
+
+    #[test]
+    fn test_extract_description_empty() {
+        assert_eq!(
+            extract_description("# Title\n\n## Usage\n", "README.md"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        assert_eq!(summarize("Short summary.\n\nOther"), "Short summary.");
+
+        let summary = summarize(
+            "Autosnap automatically make Snap package from source code written in Rust, Go or Python.",
+        );
+        assert_eq!(
+            summary,
+            "Autosnap automatically make Snap package from source code written in Rust, Go"
+        );
+        assert!(summary.len() <= SUMMARY_MAX_LEN);
+    }
+}
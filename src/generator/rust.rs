@@ -1,35 +1,214 @@
-use crate::generator::{Generator, Provider};
+use crate::generator::licenses::DependencyLock;
+use crate::generator::readme::extract_description;
+use crate::generator::{detect_license, Generator, Options, Provider};
 use crate::snap::{App, Part};
 use crate::Result;
 use cargo_lock::Lockfile;
 use cargo_toml::Manifest;
-use std::collections::BTreeMap;
+use dependency_graph::DependencyGraph;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use workspace::inherit_workspace;
+
+pub use registry::Registry;
+
+mod cfg;
+mod dependency_graph;
+mod registry;
+mod workspace;
 
 pub struct RustGenerator {
     cargo_toml: Manifest,
-    cargo_lock: Option<Lockfile>,
+    /// The packages linked by each binary (if the Cargo.lock is available)
+    linked_packages: Option<BTreeMap<String, BTreeSet<String>>>,
+    /// The toolchain pinned by the rust-toolchain file, if any
+    toolchain: Option<String>,
+    /// The minimum supported rust version (package.rust-version), if any
+    rust_version: Option<String>,
+    /// The Cargo features to enable
+    features: Vec<String>,
+    no_default_features: bool,
+    locked: bool,
+    /// The snap overrides (package.metadata.snap)
+    snap_metadata: SnapMetadata,
     source_path: PathBuf,
     source_name: String,
 }
 
 pub struct RustProvider {}
 
+/// The snap overrides set by the crate author in `[package.metadata.snap]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SnapMetadata {
+    base: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    grade: Option<String>,
+    confinement: Option<String>,
+    icon: Option<String>,
+    #[serde(default)]
+    stage_packages: Vec<String>,
+    plugs: Option<Plugs>,
+    features: Option<Vec<String>>,
+    #[serde(default)]
+    no_default_features: bool,
+    #[serde(default)]
+    use_global_lto: bool,
+}
+
+/// The plugs of the apps, either shared by all of them or set per bin
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Plugs {
+    All(Vec<String>),
+    PerBin(BTreeMap<String, Vec<String>>),
+}
+
+/// The forges on which the issues are tracked at <repository>/issues
+const FORGES: [&str; 3] = [
+    "https://github.com/",
+    "https://gitlab.com/",
+    "https://codeberg.org/",
+];
+
+/// The crates requiring plugs
+const CRATE_PLUGS: [(&str, &[&str]); 12] = [
+    ("actix-web", &["network", "network-bind"]),
+    ("alsa-sys", &["audio-playback"]),
+    ("axum", &["network", "network-bind"]),
+    ("hyper", &["network"]),
+    ("libpulse-sys", &["audio-playback"]),
+    ("reqwest", &["network"]),
+    ("rocket", &["network", "network-bind"]),
+    ("tide", &["network", "network-bind"]),
+    ("tonic", &["network"]),
+    ("ureq", &["network"]),
+    ("warp", &["network", "network-bind"]),
+    ("x11-dl", &["x11"]),
+];
+
+/// The bases the runtime libraries package names are known for
+const BASES: [&str; 4] = ["core18", "core20", "core22", "core24"];
+
+/// The sys crates linking to shared libraries: (crate, build packages, stage packages
+/// per base), since the runtime packages are named after sonames which vary across bases
+const NATIVE_CRATES: [(&str, &[&str], [&str; 4]); 4] = [
+    (
+        "alsa-sys",
+        &["libasound2-dev"],
+        ["libasound2", "libasound2", "libasound2", "libasound2t64"],
+    ),
+    (
+        "libdbus-sys",
+        &["libdbus-1-dev"],
+        ["libdbus-1-3", "libdbus-1-3", "libdbus-1-3", "libdbus-1-3"],
+    ),
+    (
+        "libsqlite3-sys",
+        &["libsqlite3-dev"],
+        [
+            "libsqlite3-0",
+            "libsqlite3-0",
+            "libsqlite3-0",
+            "libsqlite3-0",
+        ],
+    ),
+    (
+        "openssl-sys",
+        &["libssl-dev"],
+        ["libssl1.1", "libssl1.1", "libssl3", "libssl3t64"],
+    ),
+];
+
+/// The rustup release channels
+const RUST_CHANNELS: [&str; 3] = ["stable", "beta", "nightly"];
+
+/// The server frameworks (hyper & tonic are left out since they are commonly used as clients)
+const SERVER_CRATES: [&str; 5] = ["actix-web", "axum", "rocket", "tide", "warp"];
+
 impl Provider<RustGenerator> for RustProvider {
-    fn provide<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<RustGenerator> {
-        let manifest = Manifest::from_path(source_path.as_ref().join("Cargo.toml"))?;
+    fn provide<P: AsRef<Path>>(source_path: P, options: &Options) -> Result<RustGenerator> {
+        // cargo_toml doesn't know about workspace inheritance (nor rust-version),
+        // resolve it on the raw manifest before parsing it
+        let manifest_path = source_path.as_ref().join("Cargo.toml");
+        let mut raw_manifest: toml::Value = toml::from_str(&fs::read_to_string(&manifest_path)?)?;
+        inherit_workspace(&mut raw_manifest, &source_path)?;
+        let mut manifest = Manifest::from_str(&toml::to_string(&raw_manifest)?)?;
+        manifest.complete_from_path(&manifest_path)?;
+
         let lockfile = if source_path.as_ref().join("Cargo.lock").exists() {
             Some(Lockfile::load(source_path.as_ref().join("Cargo.lock"))?)
         } else {
             None
         };
 
+        let rust_version = raw_manifest
+            .get("package")
+            .and_then(|p| p.get("rust-version"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+
+        let snap_metadata = match manifest
+            .package
+            .as_ref()
+            .and_then(|p| p.metadata.as_ref())
+            .and_then(|m| m.get("snap"))
+        {
+            Some(snap) => {
+                log::debug!("Using snap overrides from package.metadata.snap");
+                snap.clone()
+                    .try_into()
+                    .map_err(|e| format!("invalid package.metadata.snap: {}", e))?
+            }
+            None => SnapMetadata::default(),
+        };
+
+        // Use the features given by the user, or else the ones set in package.metadata.snap
+        let features = options
+            .rust_features
+            .clone()
+            .or_else(|| snap_metadata.features.clone())
+            .unwrap_or_default();
+        let no_default_features =
+            options.rust_no_default_features || snap_metadata.no_default_features;
+
+        // Resolve the packages linked by each binary, using the features it requires (if any)
+        let linked_packages = match &lockfile {
+            Some(lockfile) => {
+                let graph = DependencyGraph::new(lockfile, &source_path);
+                let mut linked_packages = BTreeMap::new();
+                for bin in find_apps(&source_path, &options.source_name)?.into_keys() {
+                    let mut bin_features = features.clone();
+                    if let Some(product) = manifest
+                        .bin
+                        .iter()
+                        .find(|b| b.name.as_deref() == Some(bin.as_str()))
+                    {
+                        bin_features.extend(product.required_features.iter().cloned());
+                    }
+                    let packages =
+                        graph.linked_packages(&raw_manifest, &bin_features, !no_default_features);
+                    linked_packages.insert(bin, packages);
+                }
+                Some(linked_packages)
+            }
+            None => None,
+        };
+
         Ok(RustGenerator {
+            snap_metadata,
+            features,
+            no_default_features,
+            locked: options.rust_locked,
             cargo_toml: manifest,
-            cargo_lock: lockfile,
+            linked_packages,
+            toolchain: find_toolchain(&source_path)?,
+            rust_version,
             source_path: source_path.as_ref().to_path_buf(),
-            source_name: source_name.to_string(),
+            source_name: options.source_name.clone(),
         })
     }
 
@@ -39,6 +218,21 @@ impl Provider<RustGenerator> for RustProvider {
 }
 
 impl Generator for RustGenerator {
+    fn base(&self) -> Result<Option<String>> {
+        if let Some(base) = &self.snap_metadata.base {
+            return Ok(Some(base.clone()));
+        }
+
+        // disabling the default features, passing cargo parameters and enabling global LTO
+        // are only supported by the core22+ rust plugin
+        if self.no_default_features || self.locked || self.snap_metadata.use_global_lto {
+            log::debug!("Using core22 base as required by the cargo options");
+            return Ok(Some("core22".to_string()));
+        }
+
+        Ok(None)
+    }
+
     fn name(&self) -> Result<Option<String>> {
         Ok(self.cargo_toml.package.as_ref().cloned().map(|p| p.name))
     }
@@ -48,6 +242,9 @@ impl Generator for RustGenerator {
     }
 
     fn summary(&self) -> Result<Option<String>> {
+        if let Some(summary) = &self.snap_metadata.summary {
+            return Ok(Some(summary.clone()));
+        }
         Ok(self
             .cargo_toml
             .package
@@ -57,67 +254,345 @@ impl Generator for RustGenerator {
     }
 
     fn description(&self) -> Result<Option<String>> {
+        if let Some(description) = &self.snap_metadata.description {
+            return Ok(Some(description.clone()));
+        }
+
+        // Extract the description from the README declared by the crate
+        if let Some(readme) = self.package().and_then(|p| p.readme.as_ref()) {
+            let path = self.source_path.join(readme);
+            if path.is_file() {
+                log::debug!("Using description from {}", readme);
+                return Ok(extract_description(&fs::read_to_string(path)?, readme));
+            }
+        }
+
         Ok(None)
     }
 
     fn license(&self) -> Result<Option<String>> {
+        let package = match self.package() {
+            Some(package) => package,
+            None => return Ok(None),
+        };
+        if package.license.is_some() {
+            return Ok(package.license.clone());
+        }
+
+        // Detect the license from the license file declared by the crate
+        if let Some(license_file) = &package.license_file {
+            let path = self.source_path.join(license_file);
+            if path.is_file() {
+                let license = detect_license(&fs::read_to_string(path)?)?;
+                if let Some(license) = &license {
+                    log::debug!("Detected license {} from {}", license, license_file);
+                }
+                return Ok(license);
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn website(&self) -> Result<Option<String>> {
         Ok(self
-            .cargo_toml
-            .package
-            .as_ref()
-            .cloned()
-            .and_then(|p| p.license))
+            .package()
+            .and_then(|p| p.homepage.clone().or_else(|| p.documentation.clone())))
+    }
+
+    fn source_code(&self) -> Result<Option<String>> {
+        Ok(self.package().and_then(|p| p.repository.clone()))
+    }
+
+    fn issues(&self) -> Result<Option<String>> {
+        Ok(self
+            .package()
+            .and_then(|p| p.repository.as_ref())
+            .filter(|r| FORGES.iter().any(|f| r.starts_with(f)))
+            .map(|r| {
+                format!(
+                    "{}/issues",
+                    r.trim_end_matches('/').trim_end_matches(".git")
+                )
+            }))
+    }
+
+    fn contact(&self) -> Result<Option<String>> {
+        // the email of the first author (i.e `John Doe <john@doe.com>`)
+        Ok(self
+            .package()
+            .and_then(|p| p.authors.first())
+            .and_then(|a| Some(a.split_once('<')?.1.split_once('>')?.0.to_string())))
+    }
+
+    fn icon(&self) -> Result<Option<String>> {
+        Ok(self.snap_metadata.icon.clone())
+    }
+
+    fn grade(&self) -> Result<Option<String>> {
+        Ok(self.snap_metadata.grade.clone())
+    }
+
+    fn confinement(&self) -> Result<Option<String>> {
+        Ok(self.snap_metadata.confinement.clone())
     }
 
     fn parts(&self) -> Result<BTreeMap<String, Part>> {
         let mut parts = BTreeMap::default();
 
-        // Determinate custom build packages based on Cargo.lock
-        let build_packages = if let Some(lockfile) = &self.cargo_lock {
-            Some(find_build_packages(lockfile))
-        } else {
-            None
+        let base = self.base()?.unwrap_or_else(|| "core18".to_string());
+
+        // Determine custom build & stage packages based on the packages linked by the binaries
+        let linked_packages = self.all_linked_packages();
+        let (build_packages, mut stage_packages) = match &linked_packages {
+            Some(packages) => {
+                let (build_packages, stage_packages) = find_native_packages(packages, &base);
+                (Some(build_packages), stage_packages)
+            }
+            None => (None, Vec::new()),
+        };
+        for package in &self.snap_metadata.stage_packages {
+            if !stage_packages.contains(package) {
+                stage_packages.push(package.clone());
+            }
+        }
+
+        let mut part = Part {
+            build_packages,
+            stage_packages: Some(stage_packages).filter(|p| !p.is_empty()),
+            ..Part::new("rust")
         };
 
+        // Use the toolchain pinned by the source, or else the minimum supported rust version
+        let toolchain = self.toolchain.clone().or_else(|| {
+            self.rust_version.as_ref().map(|version| {
+                log::debug!("Using toolchain {} (package.rust-version)", version);
+                version.clone()
+            })
+        });
+        if let Some(toolchain) = toolchain {
+            match base.as_str() {
+                // the core18 plugin only accepts channels, revisions are set separately
+                "core18" if RUST_CHANNELS.contains(&toolchain.as_str()) => {
+                    part.rust_channel = Some(toolchain)
+                }
+                "core18" => part.rust_revision = Some(toolchain),
+                // the core20 plugin uses rustup, which honors the rust-toolchain file
+                "core20" => {}
+                _ => part.rust_channel = Some(toolchain),
+            }
+        }
+
+        self.set_cargo_options(&mut part)?;
+
         // Create parts
-        parts.insert(
-            self.source_name.clone(),
-            Part {
-                plugin: "rust".to_string(),
-                source: ".".to_string(),
-                build_packages,
-                stage_packages: None,
-                go_import_path: None,
-                python_version: None,
-            },
-        );
+        parts.insert(self.source_name.clone(), part);
 
         Ok(parts)
     }
 
     fn apps(&self) -> Result<BTreeMap<String, App>> {
-        find_apps(self.source_path.clone(), &self.source_name)
+        let mut apps = find_apps(self.source_path.clone(), &self.source_name)?;
+
+        // Binaries using a server framework are most likely daemons
+        if let Some(dependency) = SERVER_CRATES
+            .iter()
+            .find(|c| self.cargo_toml.dependencies.contains_key(**c))
+        {
+            let app_count = apps.len();
+            for (name, app) in apps.iter_mut() {
+                if app_count == 1 || bin_uses_crate(&self.source_path, name, dependency) {
+                    log::debug!("Set app {} as simple daemon (uses {})", name, dependency);
+                    app.daemon = Some("simple".to_string());
+                }
+            }
+        }
+
+        // Add the plugs required by the crates linked by each binary
+        for (name, app) in apps.iter_mut() {
+            let linked_packages = match self.linked_packages.as_ref().and_then(|p| p.get(name)) {
+                Some(packages) => packages,
+                None => continue,
+            };
+            for (dependency, dependency_plugs) in CRATE_PLUGS.iter() {
+                if linked_packages.contains(*dependency) {
+                    log::debug!(
+                        "Adding {:?} plugs to app {} as required by {}",
+                        dependency_plugs,
+                        name,
+                        dependency
+                    );
+                    app.add_plugs(dependency_plugs);
+                }
+            }
+        }
+
+        // Apply the plugs set by the crate author
+        for (name, app) in apps.iter_mut() {
+            let plugs = match &self.snap_metadata.plugs {
+                Some(Plugs::All(plugs)) => Some(plugs),
+                Some(Plugs::PerBin(plugs)) => plugs.get(name),
+                None => None,
+            };
+            if let Some(plugs) = plugs.filter(|p| !p.is_empty()) {
+                log::debug!(
+                    "Adding {:?} plugs to app {} (package.metadata.snap)",
+                    plugs,
+                    name
+                );
+                let plugs: Vec<&str> = plugs.iter().map(|p| p.as_str()).collect();
+                app.add_plugs(&plugs);
+            }
+        }
+
+        Ok(apps)
+    }
+
+    fn lockfiles(&self) -> Result<Vec<DependencyLock>> {
+        let path = self.source_path.join("Cargo.lock");
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![DependencyLock {
+            path,
+            linked_packages: self.all_linked_packages(),
+        }])
     }
 }
 
-fn find_build_packages(lockfile: &Lockfile) -> Vec<String> {
+impl RustGenerator {
+    fn package(&self) -> Option<&cargo_toml::Package> {
+        self.cargo_toml.package.as_ref()
+    }
+
+    // the packages linked by any of the binaries
+    fn all_linked_packages(&self) -> Option<BTreeSet<String>> {
+        self.linked_packages
+            .as_ref()
+            .map(|packages| packages.values().flatten().cloned().collect())
+    }
+
+    // set the features & cargo options supported by the base rust plugin
+    fn set_cargo_options(&self, part: &mut Part) -> Result<()> {
+        let base = self.base()?.unwrap_or_else(|| "core18".to_string());
+        let legacy_plugin = base == "core18" || base == "core20";
+
+        if !self.features.is_empty() {
+            log::debug!("Enabling features {:?}", self.features);
+            part.rust_features = Some(self.features.clone());
+        }
+
+        if self.no_default_features {
+            if legacy_plugin {
+                log::warn!("The {} rust plugin can't disable default features", base);
+            } else {
+                part.rust_no_default_features = Some(true);
+            }
+        }
+        if self.locked {
+            if legacy_plugin {
+                log::warn!("The {} rust plugin can't pass cargo parameters", base);
+            } else {
+                part.rust_cargo_parameters = Some(vec!["--locked".to_string()]);
+            }
+        }
+
+        if self.snap_metadata.use_global_lto {
+            if legacy_plugin {
+                log::warn!("The {} rust plugin can't enable global LTO", base);
+            } else {
+                log::debug!("Enabling global LTO");
+                part.rust_use_global_lto = Some(true);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the toolchain pinned using rust-toolchain.toml or the legacy rust-toolchain file
+/// (i.e `1.70.0`, `nightly-2023-06-01`)
+fn find_toolchain<P: AsRef<Path>>(source_path: P) -> Result<Option<String>> {
+    for file in ["rust-toolchain.toml", "rust-toolchain"].iter() {
+        let path = source_path.as_ref().join(file);
+        if !path.is_file() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path)?;
+        // the legacy file contains either the toolchain name or the TOML configuration
+        let toolchain = match toml::from_str::<toml::Value>(&content) {
+            Ok(config) => config
+                .get("toolchain")
+                .and_then(|t| t.get("channel"))
+                .and_then(|c| c.as_str())
+                .map(|c| c.to_string()),
+            Err(_) => content
+                .lines()
+                .map(|l| l.trim())
+                .find(|l| !l.is_empty())
+                .map(|l| l.to_string()),
+        };
+
+        if let Some(toolchain) = &toolchain {
+            log::debug!("Found toolchain {} ({})", toolchain, path.display());
+        }
+        return Ok(toolchain);
+    }
+
+    Ok(None)
+}
+
+// find the packages needed to build (& run) the given sys crates on given base
+fn find_native_packages(packages: &BTreeSet<String>, base: &str) -> (Vec<String>, Vec<String>) {
     let mut build_packages = vec!["libc6-dev".to_string()];
-    for package in &lockfile.packages {
-        for dependency in &package.dependencies {
-            if dependency.name.as_str().starts_with("openssl-")
-                && !build_packages.iter().any(|p| p == "libssl-dev")
-            {
-                // watch out for openssl-sys dependencies
-                log::debug!(
-                    "Adding libssl-dev build package as required by {}",
-                    package.name
-                );
-                build_packages.push("libssl-dev".to_string());
+    let mut stage_packages: Vec<String> = Vec::new();
+
+    let base_index = BASES.iter().position(|b| *b == base).unwrap_or_else(|| {
+        log::warn!("Unknown base {}, using {} stage packages", base, BASES[3]);
+        BASES.len() - 1
+    });
+
+    for (name, build, stage) in NATIVE_CRATES.iter() {
+        if !packages.contains(*name) {
+            continue;
+        }
+
+        let stage = stage[base_index];
+        log::debug!(
+            "Adding {:?} build packages and {} stage package as required by {}",
+            build,
+            stage,
+            name
+        );
+        for package in build.iter() {
+            if !build_packages.iter().any(|p| p == package) {
+                build_packages.push(package.to_string());
             }
         }
+        if !stage_packages.iter().any(|p| p == stage) {
+            stage_packages.push(stage.to_string());
+        }
     }
 
-    build_packages
+    (build_packages, stage_packages)
+}
+
+// whether the source of given binary (src/bin/<name>.rs) uses given crate
+fn bin_uses_crate<P: AsRef<Path>>(source_path: P, bin: &str, dependency: &str) -> bool {
+    let path = source_path
+        .as_ref()
+        .join("src")
+        .join("bin")
+        .join(format!("{}.rs", bin));
+    let identifier = dependency.replace('-', "_");
+    fs::read_to_string(path)
+        .map(|content| {
+            content.contains(&format!("use {}", identifier))
+                || content.contains(&format!("{}::", identifier))
+        })
+        .unwrap_or(false)
 }
 
 fn find_apps<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<BTreeMap<String, App>> {
@@ -131,10 +606,7 @@ fn find_apps<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<BTreeM
         log::debug!("Found single executable (name: {})", source_name);
         apps.insert(
             source_name.to_string(),
-            App {
-                command: format!("bin/{}", source_name),
-                plugs: None, // TODO
-            },
+            App::new(&format!("bin/{}", source_name)),
         );
     } else {
         for entry in fs::read_dir(source_path.as_ref().join("src").join("bin"))? {
@@ -146,10 +618,7 @@ fn find_apps<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<BTreeM
                 log::debug!("Found executable (name: {})", binary_name);
                 apps.insert(
                     binary_name.clone(),
-                    App {
-                        command: format!("bin/{}", binary_name),
-                        plugs: None,
-                    },
+                    App::new(&format!("bin/{}", binary_name)),
                 );
             }
         }
@@ -161,8 +630,6 @@ fn find_apps<P: AsRef<Path>>(source_path: P, source_name: &str) -> Result<BTreeM
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cargo_lock::{Dependency, Name, Package, Version};
-    use std::str::FromStr;
     use tempdir::TempDir;
 
     #[test]
@@ -180,6 +647,41 @@ mod tests {
         assert_eq!(app.command, "bin/autosnap");
     }
 
+    #[test]
+    fn test_server_apps() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src").join("bin"))
+            .expect("unable to create src/bin");
+        fs::write(
+            tmp_dir.path().join("src").join("bin").join("foo-server.rs"),
+            "use axum::Router;\n\nfn main() {}",
+        )
+        .expect("unable to write src/bin/foo-server.rs");
+        fs::write(
+            tmp_dir.path().join("src").join("bin").join("foo.rs"),
+            "fn main() {}",
+        )
+        .expect("unable to write src/bin/foo.rs");
+        fs::write(
+            tmp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[dependencies]\naxum = \"0.6\"\n",
+        )
+        .expect("unable to write Cargo.toml");
+
+        let options = Options {
+            source_name: "foo".to_string(),
+            ..Default::default()
+        };
+        let generator =
+            RustProvider::provide(tmp_dir.path(), &options).expect("unable to provide generator");
+        let apps = generator.apps().expect("unable to generate apps");
+        assert_eq!(
+            apps.get("foo-server").and_then(|a| a.daemon.clone()),
+            Some("simple".to_string())
+        );
+        assert_eq!(apps.get("foo").and_then(|a| a.daemon.clone()), None);
+    }
+
     #[test]
     fn test_find_apps_multiple_apps() {
         let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
@@ -215,31 +717,289 @@ mod tests {
     }
 
     #[test]
-    fn test_find_build_packages() {
-        let dependency = Dependency {
-            name: Name::from_str("openssl-sys").unwrap(),
-            version: Version::new(1, 0, 0),
-            source: None,
+    fn test_find_toolchain() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        assert_eq!(find_toolchain(tmp_dir.path()).unwrap(), None);
+
+        fs::write(
+            tmp_dir.path().join("rust-toolchain"),
+            "nightly-2023-06-01\n",
+        )
+        .expect("unable to write rust-toolchain");
+        assert_eq!(
+            find_toolchain(tmp_dir.path()).unwrap(),
+            Some("nightly-2023-06-01".to_string())
+        );
+
+        fs::write(
+            tmp_dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.70.0\"\ncomponents = [\"rustfmt\"]\n",
+        )
+        .expect("unable to write rust-toolchain.toml");
+        assert_eq!(
+            find_toolchain(tmp_dir.path()).unwrap(),
+            Some("1.70.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_toolchain_per_base() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src")).expect("unable to create src");
+        fs::write(tmp_dir.path().join("src").join("main.rs"), "fn main() {}")
+            .expect("unable to write src/main.rs");
+        fs::write(tmp_dir.path().join("rust-toolchain"), "1.70.0\n")
+            .expect("unable to write rust-toolchain");
+
+        let options = Options {
+            source_name: "foo".to_string(),
+            ..Default::default()
         };
+        let expected = [
+            ("core18", None, Some("1.70.0")),
+            ("core20", None, None),
+            ("core22", Some("1.70.0"), None),
+        ];
+        for (base, channel, revision) in expected.iter() {
+            fs::write(
+                tmp_dir.path().join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[package.metadata.snap]\nbase = \"{}\"\n",
+                    base
+                ),
+            )
+            .expect("unable to write Cargo.toml");
+
+            let generator = RustProvider::provide(tmp_dir.path(), &options)
+                .expect("unable to provide generator");
+            assert_eq!(generator.base().unwrap(), Some(base.to_string()));
+            let parts = generator.parts().expect("unable to generate parts");
+            let part = parts.get("foo").expect("part foo is not present");
+            assert_eq!(part.rust_channel.as_deref(), *channel, "{}", base);
+            assert_eq!(part.rust_revision.as_deref(), *revision, "{}", base);
+        }
+
+        // without toolchain file, the minimum supported rust version is used
+        fs::remove_file(tmp_dir.path().join("rust-toolchain"))
+            .expect("unable to remove rust-toolchain");
+        fs::write(
+            tmp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\nrust-version = \"1.70\"\n",
+        )
+        .expect("unable to write Cargo.toml");
+        let generator =
+            RustProvider::provide(tmp_dir.path(), &options).expect("unable to provide generator");
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = parts.get("foo").expect("part foo is not present");
+        assert_eq!(part.rust_channel, None);
+        assert_eq!(part.rust_revision, Some("1.70".to_string()));
+    }
+
+    #[test]
+    fn test_cargo_options() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src")).expect("unable to create src");
+        fs::write(tmp_dir.path().join("src").join("main.rs"), "fn main() {}")
+            .expect("unable to write src/main.rs");
+        fs::write(
+            tmp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[features]\ndefault = [\"tls\"]\ntls = []\nfull = [\"tls\"]\n\n[package.metadata.docs.rs]\nall-features = true\n\n[profile.release]\nlto = true\n",
+        )
+        .expect("unable to write Cargo.toml");
 
-        let package = Package {
-            name: Name::from_str("test-package").unwrap(),
-            version: Version::new(1, 0, 0),
-            source: None,
-            checksum: None,
-            dependencies: vec![dependency],
-            replace: None,
+        let options = Options {
+            source_name: "foo".to_string(),
+            ..Default::default()
         };
+        let generator =
+            RustProvider::provide(tmp_dir.path(), &options).expect("unable to provide generator");
+        // neither the docs.rs features nor the release profile are used
+        assert_eq!(generator.base().unwrap(), None);
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = parts.get("foo").expect("part foo is not present");
+        assert_eq!(part.rust_features, None);
+        assert_eq!(part.rust_use_global_lto, None);
+        assert_eq!(part.rust_no_default_features, None);
+
+        // the cargo options can be set in package.metadata.snap
+        fs::write(
+            tmp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[features]\ndefault = [\"tls\"]\ntls = []\nfull = [\"tls\"]\n\n[package.metadata.snap]\nfeatures = [\"full\"]\nuse-global-lto = true\n",
+        )
+        .expect("unable to write Cargo.toml");
+        let generator =
+            RustProvider::provide(tmp_dir.path(), &options).expect("unable to provide generator");
+        // global LTO is only supported by the core22+ rust plugin
+        assert_eq!(generator.base().unwrap(), Some("core22".to_string()));
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = serde_yaml::to_string(parts.get("foo").expect("part foo is not present"))
+            .expect("unable to serialize part");
+        assert!(part.contains("rust-features:\n  - full"));
+        assert!(part.contains("rust-use-global-lto: true"));
+        assert!(!part.contains("rust-no-default-features"));
+
+        let options = Options {
+            rust_features: Some(vec!["tls".to_string()]),
+            rust_no_default_features: true,
+            rust_locked: true,
+            ..options
+        };
+        let generator =
+            RustProvider::provide(tmp_dir.path(), &options).expect("unable to provide generator");
+        assert_eq!(generator.base().unwrap(), Some("core22".to_string()));
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = serde_yaml::to_string(parts.get("foo").expect("part foo is not present"))
+            .expect("unable to serialize part");
+        assert!(part.contains("rust-features:\n  - tls"));
+        assert!(part.contains("rust-no-default-features: true"));
+        assert!(part.contains("rust-cargo-parameters:\n  - \"--locked\""));
+    }
+
+    #[test]
+    fn test_snap_metadata() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src").join("bin"))
+            .expect("unable to create src/bin");
+        fs::write(
+            tmp_dir.path().join("src").join("bin").join("foo.rs"),
+            "fn main() {}",
+        )
+        .expect("unable to write src/bin/foo.rs");
+        fs::write(
+            tmp_dir.path().join("src").join("bin").join("food.rs"),
+            "fn main() {}",
+        )
+        .expect("unable to write src/bin/food.rs");
+        fs::write(
+            tmp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\ndescription = \"The foo crate\"\n\n[package.metadata.snap]\nsummary = \"The foo tool\"\ngrade = \"stable\"\nconfinement = \"strict\"\nicon = \"assets/foo.svg\"\nstage-packages = [\"libsqlite3-0\"]\n\n[package.metadata.snap.plugs]\nfood = [\"network-bind\"]\n",
+        )
+        .expect("unable to write Cargo.toml");
+
+        let options = Options {
+            source_name: "foo".to_string(),
+            ..Default::default()
+        };
+        let generator =
+            RustProvider::provide(tmp_dir.path(), &options).expect("unable to provide generator");
+        assert_eq!(
+            generator.summary().unwrap(),
+            Some("The foo tool".to_string())
+        );
+        assert_eq!(generator.grade().unwrap(), Some("stable".to_string()));
+        assert_eq!(generator.confinement().unwrap(), Some("strict".to_string()));
+        assert_eq!(
+            generator.icon().unwrap(),
+            Some("assets/foo.svg".to_string())
+        );
+
+        let parts = generator.parts().expect("unable to generate parts");
+        let part = parts.get("foo").expect("part foo is not present");
+        assert_eq!(part.stage_packages, Some(vec!["libsqlite3-0".to_string()]));
+
+        let apps = generator.apps().expect("unable to generate apps");
+        assert_eq!(apps.get("foo").and_then(|a| a.plugs.clone()), None);
+        assert_eq!(
+            apps.get("food").and_then(|a| a.plugs.clone()),
+            Some(vec!["network-bind".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_package_links() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src")).expect("unable to create src");
+        fs::write(tmp_dir.path().join("src").join("main.rs"), "fn main() {}")
+            .expect("unable to write src/main.rs");
+        fs::write(
+            tmp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\nauthors = [\"John Doe <john@doe.com>\"]\nreadme = \"docs/README.md\"\nrepository = \"https://github.com/foo/foo.git\"\ndocumentation = \"https://docs.rs/foo\"\n",
+        )
+        .expect("unable to write Cargo.toml");
+        fs::create_dir_all(tmp_dir.path().join("docs")).expect("unable to create docs");
+        fs::write(
+            tmp_dir.path().join("docs").join("README.md"),
+            "# Foo\n\nFoo is a tool doing foo things.\n",
+        )
+        .expect("unable to write docs/README.md");
+
+        let options = Options {
+            source_name: "foo".to_string(),
+            ..Default::default()
+        };
+        let generator =
+            RustProvider::provide(tmp_dir.path(), &options).expect("unable to provide generator");
+        assert_eq!(
+            generator.description().unwrap(),
+            Some("Foo is a tool doing foo things.".to_string())
+        );
+        assert_eq!(
+            generator.website().unwrap(),
+            Some("https://docs.rs/foo".to_string())
+        );
+        assert_eq!(
+            generator.source_code().unwrap(),
+            Some("https://github.com/foo/foo.git".to_string())
+        );
+        assert_eq!(
+            generator.issues().unwrap(),
+            Some("https://github.com/foo/foo/issues".to_string())
+        );
+        assert_eq!(
+            generator.contact().unwrap(),
+            Some("john@doe.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_native_packages_per_base() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::create_dir_all(tmp_dir.path().join("src")).expect("unable to create src");
+        fs::write(tmp_dir.path().join("src").join("main.rs"), "fn main() {}")
+            .expect("unable to write src/main.rs");
+        fs::write(
+            tmp_dir.path().join("Cargo.lock"),
+            "[[package]]\nname = \"foo\"\nversion = \"0.1.0\"\ndependencies = [\"openssl-sys 0.9.0\"]\n\n[[package]]\nname = \"openssl-sys\"\nversion = \"0.9.0\"\n",
+        )
+        .expect("unable to write Cargo.lock");
 
-        let lockfile = Lockfile {
-            version: Default::default(),
-            packages: vec![package],
-            root: None,
-            metadata: Default::default(),
-            patch: Default::default(),
+        let options = Options {
+            source_name: "foo".to_string(),
+            ..Default::default()
         };
+        for (base, stage_package) in [("core20", "libssl1.1"), ("core22", "libssl3")].iter() {
+            fs::write(
+                tmp_dir.path().join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[dependencies]\nopenssl-sys = \"0.9\"\n\n[package.metadata.snap]\nbase = \"{}\"\n",
+                    base
+                ),
+            )
+            .expect("unable to write Cargo.toml");
+
+            let generator = RustProvider::provide(tmp_dir.path(), &options)
+                .expect("unable to provide generator");
+            let parts = generator.parts().expect("unable to generate parts");
+            let part = parts.get("foo").expect("part foo is not present");
+            assert_eq!(
+                part.build_packages,
+                Some(vec!["libc6-dev".to_string(), "libssl-dev".to_string()])
+            );
+            assert_eq!(part.stage_packages, Some(vec![stage_package.to_string()]));
+        }
+    }
 
-        let build_packages = find_build_packages(&lockfile);
+    #[test]
+    fn test_find_native_packages() {
+        let packages: BTreeSet<String> = vec!["openssl".to_string(), "openssl-sys".to_string()]
+            .into_iter()
+            .collect();
+
+        let (build_packages, stage_packages) = find_native_packages(&packages, "core18");
         assert_eq!(build_packages, vec!["libc6-dev", "libssl-dev"]);
+        assert_eq!(stage_packages, vec!["libssl1.1"]);
+
+        let (_, stage_packages) = find_native_packages(&packages, "core24");
+        assert_eq!(stage_packages, vec!["libssl3t64"]);
     }
 }
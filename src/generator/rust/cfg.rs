@@ -0,0 +1,161 @@
+use crate::Result;
+use std::iter::Peekable;
+use std::slice::Iter;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    OpenParenthesis,
+    CloseParenthesis,
+    Comma,
+    Equals,
+}
+
+/// Check if given dependency target (i.e `cfg(unix)` or `x86_64-unknown-linux-gnu`)
+/// may match a linux target. The predicates which doesn't depend on the operating
+/// system (i.e `target_arch`) are considered as matching, since snaps are built for
+/// several architectures.
+pub fn matches_linux(target: &str) -> bool {
+    let target = target.trim();
+    let expression = match target
+        .strip_prefix("cfg(")
+        .and_then(|t| t.strip_suffix(')'))
+    {
+        Some(expression) => expression,
+        None => return target.contains("linux"),
+    };
+
+    let tokens = tokenize(expression);
+    let mut tokens = tokens.iter().peekable();
+    match evaluate(&mut tokens) {
+        Ok(matches) if tokens.peek().is_none() => matches.unwrap_or(true),
+        Ok(_) => {
+            log::warn!("Unable to parse dependency target `{}`", target);
+            true
+        }
+        Err(e) => {
+            log::warn!("Unable to parse dependency target `{}`: {}", target, e);
+            true
+        }
+    }
+}
+
+// evaluate the next predicate, which is None when it's not known on linux
+fn evaluate(tokens: &mut Peekable<Iter<Token>>) -> Result<Option<bool>> {
+    let name = match tokens.next() {
+        Some(Token::Identifier(name)) => name,
+        token => return Err(format!("expected identifier, found {:?}", token).into()),
+    };
+
+    match tokens.peek() {
+        Some(Token::OpenParenthesis) => {
+            tokens.next();
+            let mut values = Vec::new();
+            loop {
+                if tokens.peek() == Some(&&Token::CloseParenthesis) {
+                    tokens.next();
+                    break;
+                }
+                values.push(evaluate(tokens)?);
+                match tokens.next() {
+                    Some(Token::Comma) => {}
+                    Some(Token::CloseParenthesis) => break,
+                    token => return Err(format!("expected `,` or `)`, found {:?}", token).into()),
+                }
+            }
+
+            match (name.as_str(), values.as_slice()) {
+                ("all", values) if values.contains(&Some(false)) => Ok(Some(false)),
+                ("all", values) if values.contains(&None) => Ok(None),
+                ("all", _) => Ok(Some(true)),
+                ("any", values) if values.contains(&Some(true)) => Ok(Some(true)),
+                ("any", values) if values.contains(&None) => Ok(None),
+                ("any", _) => Ok(Some(false)),
+                ("not", [value]) => Ok(value.map(|v| !v)),
+                (name, _) => Err(format!("invalid predicate {}", name).into()),
+            }
+        }
+        Some(Token::Equals) => {
+            tokens.next();
+            let value = match tokens.next() {
+                Some(Token::String(value)) => value,
+                token => return Err(format!("expected string, found {:?}", token).into()),
+            };
+            Ok(match name.as_str() {
+                "target_os" => Some(value == "linux"),
+                "target_family" => Some(value == "unix"),
+                _ => None,
+            })
+        }
+        _ => Ok(match name.as_str() {
+            "unix" => Some(true),
+            "windows" => Some(false),
+            _ => None,
+        }),
+    }
+}
+
+fn tokenize(expression: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::OpenParenthesis),
+            ')' => tokens.push(Token::CloseParenthesis),
+            ',' => tokens.push(Token::Comma),
+            '=' => tokens.push(Token::Equals),
+            '"' => {
+                let mut value = String::new();
+                for next in chars.by_ref() {
+                    if next == '"' {
+                        break;
+                    }
+                    value.push(next);
+                }
+                tokens.push(Token::String(value));
+            }
+            _ => {
+                let mut identifier = c.to_string();
+                while let Some(next) = chars.peek() {
+                    if !next.is_alphanumeric() && *next != '_' {
+                        break;
+                    }
+                    identifier.push(*next);
+                    chars.next();
+                }
+                tokens.push(Token::Identifier(identifier));
+            }
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_linux() {
+        assert!(matches_linux("cfg(unix)"));
+        assert!(!matches_linux("cfg(windows)"));
+        assert!(matches_linux("cfg(target_os = \"linux\")"));
+        assert!(!matches_linux("cfg(target_os = \"macos\")"));
+        assert!(matches_linux("cfg(not(windows))"));
+        assert!(matches_linux(
+            "cfg(any(target_os = \"linux\", target_os = \"android\"))"
+        ));
+        assert!(!matches_linux(
+            "cfg(all(unix, not(any(target_os = \"linux\", target_os = \"android\"))))"
+        ));
+        assert!(matches_linux("cfg(target_arch = \"wasm32\")"));
+        assert!(!matches_linux(
+            "cfg(all(target_arch = \"wasm32\", windows))"
+        ));
+        assert!(matches_linux("x86_64-unknown-linux-gnu"));
+        assert!(!matches_linux("x86_64-pc-windows-msvc"));
+    }
+}
@@ -0,0 +1,251 @@
+use super::cfg::matches_linux;
+use super::Registry;
+use cargo_lock::{Lockfile, Package};
+use std::collections::BTreeSet;
+use std::path::Path;
+use toml::Value;
+
+/// The dependencies table linked into the binaries: the build-dependencies only run
+/// at build time and the dev-dependencies are only used by tests
+const DEPENDENCIES_TABLE: &str = "dependencies";
+
+/// The dependency graph of a crate, built from its Cargo.lock. The dependencies of
+/// the packages are filtered using their manifest when it's available, either
+/// vendored or in the cargo registry cache.
+pub struct DependencyGraph<'a> {
+    lockfile: &'a Lockfile,
+    registry: Registry,
+}
+
+impl<'a> DependencyGraph<'a> {
+    pub fn new<P: AsRef<Path>>(lockfile: &'a Lockfile, source_path: P) -> Self {
+        DependencyGraph {
+            lockfile,
+            registry: Registry::new(source_path),
+        }
+    }
+
+    /// The names of the packages linked by the crate of given manifest, when built
+    /// for linux with given features.
+    pub fn linked_packages(
+        &self,
+        manifest: &Value,
+        features: &[String],
+        default_features: bool,
+    ) -> BTreeSet<String> {
+        let enabled = enabled_dependencies(manifest, features, default_features);
+        let dependencies = dependency_names(manifest, Some(&enabled));
+
+        let package = manifest.get("package");
+        let name = package.and_then(|p| p.get("name")).and_then(|n| n.as_str());
+        let version = package
+            .and_then(|p| p.get("version"))
+            .and_then(|v| v.as_str());
+        let root = self.lockfile.packages.iter().find(|p| {
+            Some(p.name.as_str()) == name
+                && p.source.is_none()
+                && version.map(|v| p.version.to_string() == v).unwrap_or(true)
+        });
+
+        let mut pending: Vec<&Package> = match root {
+            Some(root) => root
+                .dependencies
+                .iter()
+                .filter(|d| dependencies.contains(d.name.as_str()))
+                .filter_map(|d| self.lockfile.packages.iter().find(|p| d.matches(p)))
+                .collect(),
+            None => {
+                log::debug!("Package {:?} not found in Cargo.lock", name);
+                self.lockfile
+                    .packages
+                    .iter()
+                    .filter(|p| dependencies.contains(p.name.as_str()))
+                    .collect()
+            }
+        };
+
+        let mut visited: BTreeSet<(String, String)> = BTreeSet::new();
+        let mut linked_packages = BTreeSet::new();
+        while let Some(package) = pending.pop() {
+            if !visited.insert((package.name.to_string(), package.version.to_string())) {
+                continue;
+            }
+            linked_packages.insert(package.name.to_string());
+            pending.extend(self.dependencies(package));
+        }
+
+        linked_packages
+    }
+
+    // the dependencies of given package built on linux, or all of the locked ones
+    // if its manifest is not available
+    fn dependencies(&self, package: &Package) -> Vec<&'a Package> {
+        let names = self
+            .registry
+            .load_manifest(package.name.as_str(), &package.version.to_string())
+            .map(|(_, manifest)| dependency_names(&manifest, None));
+
+        package
+            .dependencies
+            .iter()
+            .filter(|d| {
+                names
+                    .as_ref()
+                    .map(|names| names.contains(d.name.as_str()))
+                    .unwrap_or(true)
+            })
+            .filter_map(|d| self.lockfile.packages.iter().find(|p| d.matches(p)))
+            .collect()
+    }
+}
+
+// the names of the packages the given manifest depends on when built on linux,
+// keeping only the enabled optional ones (if known)
+fn dependency_names(manifest: &Value, enabled: Option<&BTreeSet<String>>) -> BTreeSet<String> {
+    let linux_targets = manifest
+        .get("target")
+        .and_then(|t| t.as_table())
+        .into_iter()
+        .flatten()
+        .filter(|(target, _)| matches_linux(target))
+        .map(|(_, dependencies)| dependencies);
+
+    let mut names = BTreeSet::new();
+    for dependencies in Some(manifest).into_iter().chain(linux_targets) {
+        let table = dependencies
+            .get(DEPENDENCIES_TABLE)
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flatten();
+        for (key, dependency) in table {
+            let optional = dependency.get("optional").and_then(|o| o.as_bool()) == Some(true);
+            if optional && enabled.map(|e| !e.contains(key)).unwrap_or(false) {
+                continue;
+            }
+
+            // the dependency may be renamed
+            let name = dependency
+                .get("package")
+                .and_then(|p| p.as_str())
+                .unwrap_or(key);
+            names.insert(name.to_string());
+        }
+    }
+
+    names
+}
+
+// the optional dependencies enabled by given features
+fn enabled_dependencies(
+    manifest: &Value,
+    features: &[String],
+    default_features: bool,
+) -> BTreeSet<String> {
+    let declared_features = manifest.get("features").and_then(|f| f.as_table());
+
+    let mut pending: Vec<String> = features.to_vec();
+    if default_features {
+        pending.push("default".to_string());
+    }
+
+    let mut visited = BTreeSet::new();
+    let mut dependencies = BTreeSet::new();
+    while let Some(feature) = pending.pop() {
+        if !visited.insert(feature.clone()) {
+            continue;
+        }
+
+        if let Some(dependency) = feature.strip_prefix("dep:") {
+            dependencies.insert(dependency.to_string());
+        } else if let Some((dependency, _)) = feature.split_once('/') {
+            // `foo?/bar` doesn't enable foo
+            if !dependency.ends_with('?') {
+                dependencies.insert(dependency.to_string());
+            }
+        } else if let Some(enabled) = declared_features.and_then(|f| f.get(&feature)) {
+            let enabled = enabled.as_array().into_iter().flatten();
+            pending.extend(enabled.filter_map(|f| f.as_str()).map(|f| f.to_string()));
+        } else {
+            // optional dependencies have an implicit feature
+            dependencies.insert(feature);
+        }
+    }
+
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::str::FromStr;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_linked_packages() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        let vendor_path = tmp_dir.path().join("vendor").join("foo-sys");
+        fs::create_dir_all(&vendor_path).expect("unable to create vendor/foo-sys");
+        fs::write(
+            vendor_path.join("Cargo.toml"),
+            "[package]\nname = \"foo-sys\"\nversion = \"0.0.1\"\n\n[build-dependencies]\npkg-config = \"0.3\"\n\n[target.'cfg(windows)'.build-dependencies]\nvcpkg = \"0.2\"\n",
+        )
+        .expect("unable to write vendor/foo-sys/Cargo.toml");
+
+        let lockfile = Lockfile::from_str(
+            "[[package]]
+name = \"foo\"
+version = \"0.1.0\"
+dependencies = [\"foo-sys 0.0.1\", \"lazy 0.0.1\", \"serde 0.0.1\", \"tester 0.0.1\", \"winapi 0.0.1\"]
+
+[[package]]
+name = \"foo-sys\"
+version = \"0.0.1\"
+dependencies = [\"pkg-config 0.0.1\", \"vcpkg 0.0.1\"]
+
+[[package]]
+name = \"lazy\"
+version = \"0.0.1\"
+
+[[package]]
+name = \"pkg-config\"
+version = \"0.0.1\"
+
+[[package]]
+name = \"serde\"
+version = \"0.0.1\"
+
+[[package]]
+name = \"tester\"
+version = \"0.0.1\"
+
+[[package]]
+name = \"vcpkg\"
+version = \"0.0.1\"
+
+[[package]]
+name = \"winapi\"
+version = \"0.0.1\"
+",
+        )
+        .expect("unable to parse Cargo.lock");
+
+        let manifest: Value = toml::from_str(
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[features]\nlazy = [\"dep:lazy\"]\n\n[dependencies]\nfoo-sys = \"0.0.1\"\nlazy = { version = \"0.0.1\", optional = true }\nserde = { version = \"0.0.1\", optional = true }\n\n[dev-dependencies]\ntester = \"0.0.1\"\n\n[target.'cfg(windows)'.dependencies]\nwinapi = \"0.0.1\"\n",
+        )
+        .expect("unable to parse manifest");
+
+        let graph = DependencyGraph::new(&lockfile, tmp_dir.path());
+        let packages = graph.linked_packages(&manifest, &[], true);
+        assert_eq!(
+            packages.into_iter().collect::<Vec<String>>(),
+            vec!["foo-sys"]
+        );
+
+        let packages = graph.linked_packages(&manifest, &["lazy".to_string()], true);
+        assert_eq!(
+            packages.into_iter().collect::<Vec<String>>(),
+            vec!["foo-sys", "lazy"]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// The local copies of the crates: the vendored ones and the cargo registry cache
+pub struct Registry {
+    directories: Vec<PathBuf>,
+}
+
+impl Registry {
+    pub fn new<P: AsRef<Path>>(source_path: P) -> Self {
+        let mut directories = vec![source_path.as_ref().join("vendor")];
+
+        let cargo_home = env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")));
+        if let Some(cargo_home) = cargo_home {
+            if let Ok(entries) = fs::read_dir(cargo_home.join("registry").join("src")) {
+                directories.extend(entries.filter_map(|e| e.ok()).map(|e| e.path()));
+            }
+        }
+
+        Registry { directories }
+    }
+
+    /// Load the manifest of given crate version, along with the crate directory.
+    pub fn load_manifest(&self, name: &str, version: &str) -> Option<(PathBuf, Value)> {
+        let crate_directories = [format!("{}-{}", name, version), name.to_string()];
+
+        for directory in &self.directories {
+            for crate_directory in crate_directories.iter() {
+                let path = directory.join(crate_directory);
+                let manifest = match fs::read_to_string(path.join("Cargo.toml"))
+                    .ok()
+                    .and_then(|content| toml::from_str::<Value>(&content).ok())
+                {
+                    Some(manifest) => manifest,
+                    None => continue,
+                };
+
+                // vendored crates may not be suffixed by their version
+                let manifest_version = manifest
+                    .get("package")
+                    .and_then(|p| p.get("version"))
+                    .and_then(|v| v.as_str());
+                if manifest_version == Some(version) {
+                    return Some((path, manifest));
+                }
+            }
+        }
+
+        None
+    }
+}
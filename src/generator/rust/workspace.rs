@@ -0,0 +1,250 @@
+use crate::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// The package fields that can be inherited from the workspace
+const INHERITABLE_FIELDS: [&str; 16] = [
+    "authors",
+    "categories",
+    "description",
+    "documentation",
+    "edition",
+    "exclude",
+    "homepage",
+    "include",
+    "keywords",
+    "license",
+    "license-file",
+    "publish",
+    "readme",
+    "repository",
+    "rust-version",
+    "version",
+];
+
+/// The package fields holding a path relative to the manifest
+const PATH_FIELDS: [&str; 2] = ["license-file", "readme"];
+
+const DEPENDENCIES_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Resolve the fields (`version.workspace = true`) and dependencies
+/// (`foo = { workspace = true }`) that the manifest located in given source
+/// inherits from its workspace.
+pub fn inherit_workspace<P: AsRef<Path>>(manifest: &mut Value, source_path: P) -> Result<()> {
+    if !inherits_workspace(manifest) {
+        return Ok(());
+    }
+
+    let (root_path, workspace) = match find_workspace(manifest, &source_path)? {
+        Some(workspace) => workspace,
+        None => return Err("Cannot find the workspace the manifest inherits from.".into()),
+    };
+    log::debug!("Inheriting workspace fields from {}", root_path.display());
+
+    let workspace_package = workspace.get("package");
+    if let Some(package) = manifest.get_mut("package").and_then(|p| p.as_table_mut()) {
+        for field in INHERITABLE_FIELDS.iter() {
+            if !package.get(*field).map(is_inherited).unwrap_or(false) {
+                continue;
+            }
+
+            let mut value = workspace_package
+                .and_then(|p| p.get(*field))
+                .cloned()
+                .ok_or_else(|| format!("workspace.package.{} is not set", field))?;
+
+            // paths are relative to the workspace root
+            if PATH_FIELDS.contains(field) {
+                if let Value::String(path) = &value {
+                    value = Value::String(root_path.join(path).to_string_lossy().to_string());
+                }
+            }
+            package.insert(field.to_string(), value);
+        }
+    }
+
+    let workspace_dependencies = workspace.get("dependencies");
+    for table in DEPENDENCIES_TABLES.iter() {
+        if let Some(dependencies) = manifest.get_mut(*table) {
+            inherit_dependencies(dependencies, workspace_dependencies)?;
+        }
+    }
+    if let Some(targets) = manifest.get_mut("target").and_then(|t| t.as_table_mut()) {
+        for (_, target) in targets.iter_mut() {
+            for table in DEPENDENCIES_TABLES.iter() {
+                if let Some(dependencies) = target.get_mut(*table) {
+                    inherit_dependencies(dependencies, workspace_dependencies)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn inherit_dependencies(dependencies: &mut Value, workspace: Option<&Value>) -> Result<()> {
+    let dependencies = match dependencies.as_table_mut() {
+        Some(dependencies) => dependencies,
+        None => return Ok(()),
+    };
+
+    for (name, dependency) in dependencies.iter_mut() {
+        if !is_inherited(dependency) {
+            continue;
+        }
+
+        let inherited = workspace
+            .and_then(|w| w.get(name))
+            .ok_or_else(|| format!("workspace.dependencies.{} is not set", name))?;
+        let mut resolved = match inherited {
+            Value::String(version) => {
+                let mut table = toml::value::Table::new();
+                table.insert("version".to_string(), Value::String(version.clone()));
+                table
+            }
+            Value::Table(table) => table.clone(),
+            _ => return Err(format!("invalid workspace.dependencies.{}", name).into()),
+        };
+
+        // the member can add features and make the dependency optional
+        for (key, value) in dependency.as_table().into_iter().flatten() {
+            match (key.as_str(), value) {
+                ("workspace", _) => {}
+                ("features", Value::Array(features)) => {
+                    let all_features = resolved
+                        .entry("features".to_string())
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(all_features) = all_features {
+                        all_features.extend(features.iter().cloned());
+                    }
+                }
+                (key, value) => {
+                    resolved.insert(key.to_string(), value.clone());
+                }
+            }
+        }
+
+        *dependency = Value::Table(resolved);
+    }
+
+    Ok(())
+}
+
+// find the workspace (root path & [workspace] table) of the manifest: the manifest itself,
+// the one set by package.workspace, or the first one found in the parent directories
+fn find_workspace<P: AsRef<Path>>(
+    manifest: &Value,
+    source_path: P,
+) -> Result<Option<(PathBuf, Value)>> {
+    if let Some(workspace) = manifest.get("workspace") {
+        return Ok(Some((
+            source_path.as_ref().to_path_buf(),
+            workspace.clone(),
+        )));
+    }
+
+    let source_path = source_path.as_ref().canonicalize()?;
+    if let Some(path) = manifest
+        .get("package")
+        .and_then(|p| p.get("workspace"))
+        .and_then(|w| w.as_str())
+    {
+        return load_workspace(&source_path.join(path));
+    }
+
+    for directory in source_path.ancestors().skip(1) {
+        if let Some(workspace) = load_workspace(directory)? {
+            return Ok(Some(workspace));
+        }
+    }
+
+    Ok(None)
+}
+
+fn load_workspace(directory: &Path) -> Result<Option<(PathBuf, Value)>> {
+    let path = directory.join("Cargo.toml");
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let manifest: Value = toml::from_str(&fs::read_to_string(&path)?)?;
+    Ok(manifest
+        .get("workspace")
+        .map(|workspace| (directory.to_path_buf(), workspace.clone())))
+}
+
+fn inherits_workspace(manifest: &Value) -> bool {
+    let package_inherits = manifest
+        .get("package")
+        .and_then(|p| p.as_table())
+        .map(|p| p.values().any(is_inherited))
+        .unwrap_or(false);
+
+    let tables = DEPENDENCIES_TABLES
+        .iter()
+        .filter_map(|t| manifest.get(*t))
+        .chain(
+            manifest
+                .get("target")
+                .and_then(|t| t.as_table())
+                .into_iter()
+                .flat_map(|targets| targets.values())
+                .flat_map(|target| {
+                    DEPENDENCIES_TABLES
+                        .iter()
+                        .filter_map(move |t| target.get(*t))
+                }),
+        );
+
+    package_inherits
+        || tables
+            .filter_map(|t| t.as_table())
+            .any(|t| t.values().any(is_inherited))
+}
+
+// whether the value is inherited from the workspace (i.e `{ workspace = true }`)
+fn is_inherited(value: &Value) -> bool {
+    value.get("workspace").and_then(|w| w.as_bool()) == Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_inherit_workspace() {
+        let tmp_dir = TempDir::new("autosnap").expect("unable to create temporary dir");
+        fs::write(
+            tmp_dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"foo\"]\n\n[workspace.package]\nversion = \"1.2.0\"\nlicense = \"MIT\"\nreadme = \"README.md\"\n\n[workspace.dependencies]\nserde = { version = \"1.0\", features = [\"derive\"] }\nlog = \"0.4\"\n",
+        )
+        .expect("unable to write Cargo.toml");
+        fs::create_dir_all(tmp_dir.path().join("foo")).expect("unable to create foo");
+
+        let mut manifest: Value = toml::from_str(
+            "[package]\nname = \"foo\"\nversion.workspace = true\nlicense = { workspace = true }\nreadme.workspace = true\n\n[dependencies]\nserde = { workspace = true, features = [\"rc\"] }\nlog.workspace = true\nanyhow = \"1\"\n",
+        )
+        .expect("unable to parse manifest");
+        inherit_workspace(&mut manifest, tmp_dir.path().join("foo"))
+            .expect("unable to inherit workspace");
+
+        let package = manifest.get("package").expect("package not set");
+        assert_eq!(package.get("version"), Some(&Value::from("1.2.0")));
+        assert_eq!(package.get("license"), Some(&Value::from("MIT")));
+        let readme = package.get("readme").and_then(|r| r.as_str()).unwrap();
+        assert!(readme.ends_with("README.md") && Path::new(readme).is_absolute());
+
+        let dependencies = manifest.get("dependencies").expect("dependencies not set");
+        assert_eq!(
+            dependencies.get("serde").and_then(|s| s.get("features")),
+            Some(&Value::from(vec!["derive", "rc"]))
+        );
+        assert_eq!(
+            dependencies.get("log").and_then(|s| s.get("version")),
+            Some(&Value::from("0.4"))
+        );
+        assert_eq!(dependencies.get("anyhow"), Some(&Value::from("1")));
+    }
+}
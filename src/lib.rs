@@ -27,7 +27,7 @@ pub fn fetch_source(source_url: &Url) -> Result<PathBuf> {
     let source_name = source_url
         .path_segments()
         .unwrap()
-        .last()
+        .next_back()
         .unwrap()
         .replace(".git", "");
     let path = cwd.join(source_name);
@@ -45,7 +45,7 @@ pub fn fetch_source(source_url: &Url) -> Result<PathBuf> {
 /// use url::Url;
 /// use autosnap::generator::{Options, Version};
 /// let path = fetch_source(&Url::parse("https://www.github.com/creekorful/osync.git").unwrap()).unwrap();
-/// let snap = package_source(&path, &Options {source_name: "".to_string(), snap_version: Version::Git}).unwrap();
+/// let snap = package_source(&path, &Options {snap_version: Version::Git, ..Default::default()}).unwrap();
 /// ```
 pub fn package_source<P: AsRef<Path>>(source_path: P, options: &Options) -> Result<File> {
     // convert . into current dir
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::path::Path;
+use std::{fs, io};
 
 pub const SNAPCRAFT_YAML: &str = "snapcraft.yaml";
+pub const THIRD_PARTY_LICENSES: &str = "THIRD_PARTY_LICENSES";
+pub const GUI_DIRECTORY: &str = "snap/gui";
 
 /// This structure represent a Snap (snapcraft.yaml) file.
 /// See this [link](https://snapcraft.io/docs/snapcraft-yaml-reference) for more information.
@@ -9,14 +13,43 @@ pub const SNAPCRAFT_YAML: &str = "snapcraft.yaml";
 pub struct File {
     pub name: String,
     pub base: String,
-    pub version: String,
-    pub summary: String,
-    pub description: String,
+    #[serde(rename = "adopt-info")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adopt_info: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub license: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(rename = "source-code")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issues: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
     pub grade: String,
     pub confinement: String,
     pub parts: BTreeMap<String, Part>,
     pub apps: BTreeMap<String, App>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub slots: BTreeMap<String, Slot>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub hooks: BTreeMap<String, Hook>,
+    /// The third-party licenses report, to be written as THIRD_PARTY_LICENSES
+    #[serde(skip)]
+    pub third_party_licenses: Option<String>,
+    /// The desktop entries & icon (by file name), to be written in snap/gui
+    #[serde(skip)]
+    pub gui_files: BTreeMap<String, Vec<u8>>,
 }
 
 impl File {
@@ -24,16 +57,37 @@ impl File {
         File {
             name: name.to_string(),
             base: "core18".to_string(),
-            version: "TODO".to_string(),
-            summary: "TODO".to_string(),
-            description: "TODO".to_string(),
+            adopt_info: None,
+            version: Some("TODO".to_string()),
+            summary: Some("TODO".to_string()),
+            description: Some("TODO".to_string()),
             license: "TODO".to_string(),
+            icon: None,
+            website: None,
+            source_code: None,
+            issues: None,
+            contact: None,
             grade: "devel".to_string(),
             confinement: "devmode".to_string(), // TODO switch to strict when we manage plugs
             parts: Default::default(),
             apps: Default::default(),
+            slots: Default::default(),
+            hooks: Default::default(),
+            third_party_licenses: None,
+            gui_files: Default::default(),
         }
     }
+
+    /// Write the graphical resources inside the snap/gui directory of given project,
+    /// where snapcraft picks them up.
+    pub fn write_gui_files<P: AsRef<Path>>(&self, project_path: P) -> io::Result<()> {
+        let gui_path = project_path.as_ref().join(GUI_DIRECTORY);
+        for (name, content) in &self.gui_files {
+            fs::create_dir_all(&gui_path)?;
+            fs::write(gui_path.join(name), content)?;
+        }
+        Ok(())
+    }
 }
 
 /// This structure represent a Part.
@@ -42,18 +96,92 @@ impl File {
 pub struct Part {
     pub plugin: String,
     pub source: String,
+    #[serde(rename = "source-subdir")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_subdir: Option<String>,
     #[serde(rename = "build-packages")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub build_packages: Option<Vec<String>>,
     #[serde(rename = "stage-packages")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stage_packages: Option<Vec<String>>,
+    #[serde(rename = "build-snaps")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_snaps: Option<Vec<String>>,
+    #[serde(rename = "build-environment")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_environment: Option<Vec<BTreeMap<String, String>>>,
     #[serde(rename = "go-importpath")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub go_import_path: Option<String>,
+    #[serde(rename = "go-channel")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub go_channel: Option<String>,
     #[serde(rename = "python-version")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub python_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requirements: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<Vec<String>>,
+    #[serde(rename = "python-requirements")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub python_requirements: Option<Vec<String>>,
+    #[serde(rename = "python-constraints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub python_constraints: Option<Vec<String>>,
+    #[serde(rename = "rust-channel")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_channel: Option<String>,
+    #[serde(rename = "rust-revision")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_revision: Option<String>,
+    #[serde(rename = "rust-features")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_features: Option<Vec<String>>,
+    #[serde(rename = "rust-no-default-features")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_no_default_features: Option<bool>,
+    #[serde(rename = "rust-use-global-lto")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_use_global_lto: Option<bool>,
+    #[serde(rename = "rust-cargo-parameters")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_cargo_parameters: Option<Vec<String>>,
+    #[serde(rename = "parse-info")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_info: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage: Option<Vec<String>>,
+}
+
+impl Part {
+    pub fn new(plugin: &str) -> Part {
+        Part {
+            plugin: plugin.to_string(),
+            source: ".".to_string(),
+            source_subdir: None,
+            build_packages: None,
+            stage_packages: None,
+            build_snaps: None,
+            build_environment: None,
+            go_import_path: None,
+            go_channel: None,
+            python_version: None,
+            requirements: None,
+            constraints: None,
+            python_requirements: None,
+            python_constraints: None,
+            rust_channel: None,
+            rust_revision: None,
+            rust_features: None,
+            rust_no_default_features: None,
+            rust_use_global_lto: None,
+            rust_cargo_parameters: None,
+            parse_info: None,
+            stage: None,
+        }
+    }
 }
 
 /// This structure represent an Application.
@@ -63,4 +191,66 @@ pub struct App {
     pub command: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub plugs: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slots: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<String>,
+    #[serde(rename = "common-id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub common_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daemon: Option<String>,
+    #[serde(rename = "restart-condition")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_condition: Option<String>,
+    #[serde(rename = "bus-name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bus_name: Option<String>,
+}
+
+impl App {
+    pub fn new(command: &str) -> App {
+        App {
+            command: command.to_string(),
+            plugs: None,
+            slots: None,
+            desktop: None,
+            common_id: None,
+            daemon: None,
+            restart_condition: None,
+            bus_name: None,
+        }
+    }
+
+    /// Add given plugs to the application, skipping the ones already present.
+    pub fn add_plugs(&mut self, plugs: &[&str]) {
+        let app_plugs = self.plugs.get_or_insert_with(Vec::new);
+        for plug in plugs {
+            if !app_plugs.iter().any(|p| p == plug) {
+                app_plugs.push(plug.to_string());
+            }
+        }
+    }
+}
+
+/// This structure represent a Slot.
+/// See this [link](https://snapcraft.io/docs/interface-management) for more information.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct Slot {
+    pub interface: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bus: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// This structure represent a Hook.
+/// See this [link](https://snapcraft.io/docs/supported-snap-hooks) for more information.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct Hook {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugs: Option<Vec<String>>,
+    /// The hook script, to be written as snap/hooks/<name>
+    #[serde(skip)]
+    pub script: Option<String>,
 }